@@ -1,23 +1,92 @@
-use crate::{card, player};
+use crate::{card, observation, player};
+use serde::{Deserialize, Serialize};
 
 /// Represents the possible actions a user can take.
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum UserAction {
     Draw,
     Play(usize),
+    /// Plays the wild card at the index and declares the colour in play.
+    PlayWild(usize, card::Colour),
+    /// Declares UNO before playing the second to last card.
+    Uno,
+    /// Ends the turn without playing, e.g. to keep a card that was just drawn.
+    Pass,
+}
+
+/// Asks the game to do something other than take a move.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Command {
+    /// Suspends the game before the move is made, so that it can be saved and resumed.
+    Suspend,
+    /// Takes back the actor's last turn, rewinding the game to the start of their
+    /// previous turn when the game keeps an undo history.
+    Undo,
 }
 
 /// Defines the behavior of an actor in the game.
 pub trait Actor {
     /// Determines the action the actor will take on their turn.
+    /// `legal_actions` lists every action the game accepts, and `observation` is
+    /// what the actor can see of the game.
     ///
     /// Returns a `UserAction` representing the chosen action.
-    fn get_turn_action(&mut self) -> UserAction;
+    fn get_turn_action(
+        &mut self,
+        legal_actions: &[UserAction],
+        observation: &observation::Observation,
+    ) -> UserAction;
 
-    /// Determines the color choice for the actor.
+    /// Determines whether the actor plays the card it just drew, which is playable
+    /// and is the last card of its hand. `legal_actions` lists every action the game accepts.
+    ///
+    /// Returns `UserAction::Play` or `UserAction::PlayWild` to play the card,
+    /// `UserAction::Uno` to declare UNO first, or `UserAction::Pass` to keep it.
+    fn get_drawn_card_action(
+        &mut self,
+        legal_actions: &[UserAction],
+        observation: &observation::Observation,
+    ) -> UserAction;
+
+    /// Determines how the actor answers a pending draw penalty when stacking is enabled.
+    /// `legal_actions` lists every action the game accepts.
+    ///
+    /// Returns `UserAction::Play` or `UserAction::PlayWild` with the index of a draw card
+    /// to pass the penalty on, `UserAction::Draw` to take `penalty` cards, or
+    /// `UserAction::Uno` to declare UNO before stacking the second-to-last card.
+    fn get_stack_action(
+        &mut self,
+        penalty: usize,
+        legal_actions: &[UserAction],
+        observation: &observation::Observation,
+    ) -> UserAction;
+
+    /// Determines whether the actor challenges the Wild Draw card played against them.
+    ///
+    /// Returns `true` to challenge it, or `false` to take the penalty.
+    fn decide_challenge(&mut self) -> bool;
+
+    /// Determines whether the actor catches a player who forgot to declare UNO.
+    ///
+    /// Returns `true` to make the player with ID `offender_id` draw the penalty cards.
+    fn catch_missed_uno(&mut self, offender_id: usize) -> bool;
+
+    /// Determines whether the actor jumps in out of turn with the card at `card_index`,
+    /// which is identical to the card just played.
+    ///
+    /// Returns `true` to play the card. Jumping in with the second-to-last card declares UNO.
+    fn decide_jump_in(&mut self, card_index: usize) -> bool;
+
+    /// Determines whose hand the actor takes after playing a 7 with the Seven-O rule.
+    ///
+    /// `hand_sizes` holds the number of cards of every player, indexed by ID.
+    /// Returns the ID of the player to swap hands with.
+    fn get_swap_target(&mut self, hand_sizes: &[usize]) -> usize;
+
+    /// Determines the color choice for the actor after playing a wild card.
     ///
     /// Returns a `card::Colour` representing the chosen color.
-    fn get_color_choice(&mut self) -> card::Colour;
+    fn get_color_choice(&mut self, observation: &observation::Observation) -> card::Colour;
 
     /// Performs any actions required before the actor's turn.
     fn pre_turn_action(&mut self);
@@ -39,4 +108,21 @@ pub trait Actor {
     ///
     /// Returns a `usize` representing the actor's ID.
     fn get_id(&self) -> usize;
+
+    /// Returns the internal state of the actor to save with the game, e.g. the state of
+    /// its random number generator. The player's hand is saved separately.
+    fn save_state(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Returns the command the actor gave instead of answering the decision it was just
+    /// asked for, if any. The game then ignores the answer.
+    fn take_command(&mut self) -> Option<Command> {
+        None
+    }
+
+    /// Restores the internal state returned by `save_state`.
+    fn load_state(&mut self, _state: serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
 }
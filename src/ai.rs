@@ -1,9 +1,26 @@
-use crate::{actor, card, player};
+use crate::{actor, card, deck, observation, player, strategy};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// Probability that the AI remembers to declare UNO.
+const UNO_RECALL_PROBABILITY: f64 = 0.9;
+
+/// Internal state of an AI player saved with the game.
+#[derive(Serialize, Deserialize)]
+struct AiState {
+    has_considered_uno: bool,
+    rng: ChaCha8Rng,
+}
 
 pub struct AiActor {
     id: usize,
-    next_card_to_play: usize,
+    has_considered_uno: bool,
     player: player::Player,
+    strategy: Box<dyn strategy::Strategy>,
+    rng: ChaCha8Rng,
+    /// Receives the ID of the AI and the reason for every move it makes.
+    on_rationale: Option<fn(usize, &str)>,
 }
 
 impl actor::Actor for AiActor {
@@ -18,52 +35,77 @@ impl actor::Actor for AiActor {
         &mut self.player
     }
 
-    fn get_turn_action(&mut self) -> actor::UserAction {
-        let card_to_play = self.next_card_to_play;
-        if card_to_play < self.player.get_number_of_cards() {
-            self.next_card_to_play += 1;
-            actor::UserAction::Play(card_to_play)
-        } else {
-            actor::UserAction::Draw
+    fn get_turn_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        if legal_actions.contains(&actor::UserAction::Uno) && self.remembers_uno() {
+            self.explain(actor::UserAction::Uno, strategy::UNO_REASON, observation);
+            return actor::UserAction::Uno;
         }
+        let choice = self.choose_action(legal_actions, observation);
+        let action = choice.action.unwrap_or(actor::UserAction::Draw);
+        self.explain(action, &choice.reason, observation);
+        action
     }
 
-    fn get_color_choice(&mut self) -> card::Colour {
-        struct ColourCount {
-            colour: card::Colour,
-            count: usize,
+    fn get_drawn_card_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        if legal_actions.contains(&actor::UserAction::Uno) && self.remembers_uno() {
+            self.explain(actor::UserAction::Uno, strategy::UNO_REASON, observation);
+            return actor::UserAction::Uno;
         }
-        let mut red = ColourCount {
-            colour: card::Colour::Red,
-            count: 0,
-        };
-        let mut green = ColourCount {
-            colour: card::Colour::Green,
-            count: 0,
-        };
-        let mut blue = ColourCount {
-            colour: card::Colour::Blue,
-            count: 0,
-        };
-        let mut yellow = ColourCount {
-            colour: card::Colour::Yellow,
-            count: 0,
-        };
-        for card in self.player.get_hand() {
-            match card.colour {
-                card::Colour::Red => red.count += 1,
-                card::Colour::Green => green.count += 1,
-                card::Colour::Blue => blue.count += 1,
-                card::Colour::Yellow => yellow.count += 1,
-                card::Colour::Wild => {}
-            }
+        let choice = self.choose_action(legal_actions, observation);
+        let action = choice.action.unwrap_or(actor::UserAction::Pass);
+        self.explain(action, &choice.reason, observation);
+        action
+    }
+
+    fn get_stack_action(
+        &mut self,
+        _penalty: usize,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        if legal_actions.contains(&actor::UserAction::Uno) && self.remembers_uno() {
+            self.explain(actor::UserAction::Uno, strategy::UNO_REASON, observation);
+            return actor::UserAction::Uno;
         }
+        let choice = self.choose_action(legal_actions, observation);
+        let action = choice.action.unwrap_or(actor::UserAction::Draw);
+        self.explain(action, &choice.reason, observation);
+        action
+    }
+
+    fn decide_challenge(&mut self) -> bool {
+        false // A failed challenge costs two extra cards, so play it safe
+    }
+
+    fn catch_missed_uno(&mut self, _offender_id: usize) -> bool {
+        true
+    }
+
+    fn decide_jump_in(&mut self, _card_index: usize) -> bool {
+        true
+    }
 
-        [red, green, blue, yellow]
+    fn get_swap_target(&mut self, hand_sizes: &[usize]) -> usize {
+        hand_sizes
             .iter()
-            .max_by(|a, b| a.count.cmp(&b.count))
-            .unwrap() // The iterator is never empty
-            .colour
+            .enumerate()
+            .filter(|(id, _)| *id != self.id)
+            .min_by_key(|(_, size)| **size)
+            .map(|(id, _)| id)
+            .unwrap_or(self.id)
+    }
+
+    fn get_color_choice(&mut self, observation: &observation::Observation) -> card::Colour {
+        self.strategy
+            .choose_colour(self.player.get_hand(), observation, &mut self.rng)
     }
 
     fn pre_turn_action(&mut self) {
@@ -71,16 +113,76 @@ impl actor::Actor for AiActor {
     }
 
     fn post_turn_action(&mut self) {
-        self.next_card_to_play = 0;
+        self.has_considered_uno = false;
+    }
+
+    fn save_state(&self) -> serde_json::Value {
+        let state = AiState {
+            has_considered_uno: self.has_considered_uno,
+            rng: self.rng.clone(),
+        };
+        serde_json::to_value(state).unwrap() // The state only holds serializable fields
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<(), String> {
+        let state: AiState = serde_json::from_value(state).map_err(|e| e.to_string())?;
+        self.has_considered_uno = state.has_considered_uno;
+        self.rng = state.rng;
+        Ok(())
     }
 }
 
 impl AiActor {
-    pub fn new(id: usize) -> AiActor {
+    /// Decides, once per turn, whether to declare UNO when about to play down to one card.
+    fn remembers_uno(&mut self) -> bool {
+        if self.has_considered_uno {
+            return false;
+        }
+        self.has_considered_uno = true;
+        self.rng.gen_bool(UNO_RECALL_PROBABILITY)
+    }
+
+    fn choose_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> strategy::Choice {
+        self.strategy.choose_action(
+            self.player.get_hand(),
+            legal_actions,
+            observation,
+            &mut self.rng,
+        )
+    }
+
+    /// Reports `action` and the `reason` for it to the rationale callback, if any.
+    fn explain(
+        &self,
+        action: actor::UserAction,
+        reason: &str,
+        observation: &observation::Observation,
+    ) {
+        if let Some(on_rationale) = self.on_rationale {
+            let hand = self.player.get_hand();
+            let action = strategy::describe(hand, action, observation);
+            on_rationale(self.id, &format!("{action}: {reason}"));
+        }
+    }
+
+    pub fn new(id: usize, strategy: Box<dyn strategy::Strategy>, seed: Option<u64>) -> AiActor {
         AiActor {
             id,
-            next_card_to_play: 0,
+            has_considered_uno: false,
             player: player::Player::new(),
+            strategy,
+            rng: deck::new_rng(seed),
+            on_rationale: None,
         }
     }
+
+    /// Makes the AI call `on_rationale` with its ID and the reason for every move it makes.
+    pub fn with_rationale(mut self, on_rationale: fn(usize, &str)) -> AiActor {
+        self.on_rationale = Some(on_rationale);
+        self
+    }
 }
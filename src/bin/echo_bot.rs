@@ -0,0 +1,33 @@
+//! Reference bot for the external bot protocol, see `uno::bot::BotActor` in the library.
+//!
+//! It declares UNO when it can, otherwise it echoes the first legal action back, and
+//! it declares red after every wild card. Run it with `uno --bot path/to/echo_bot`.
+
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let request: Value = serde_json::from_str(&line?)?;
+        let reply = match request["type"].as_str() {
+            Some("action") => {
+                let legal_actions = request["legal_actions"].as_array().cloned();
+                let legal_actions = legal_actions.unwrap_or_default();
+                let uno = Value::from("Uno");
+                if legal_actions.contains(&uno) {
+                    uno
+                } else {
+                    legal_actions
+                        .first()
+                        .cloned()
+                        .unwrap_or(Value::from("Draw"))
+                }
+            }
+            _ => Value::from("Red"),
+        };
+        writeln!(stdout, "{reply}")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use crate::actor::Actor;
+use crate::{actor, ai, card, observation, player, strategy};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Default time a bot has to reply to a request.
+pub const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// Request sent to a bot, one JSON object per line.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a> {
+    /// Asks for a move, the bot replies with one of `legal_actions`.
+    Action {
+        decision: Decision,
+        hand: &'a [card::Card],
+        legal_actions: &'a [actor::UserAction],
+        observation: &'a observation::Observation,
+    },
+    /// Asks for the colour to declare after a wild card, the bot replies with a colour.
+    Colour {
+        hand: &'a [card::Card],
+        observation: &'a observation::Observation,
+    },
+}
+
+/// Which decision a move request is for.
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Decision {
+    /// The bot's regular turn.
+    Turn,
+    /// The bot drew a card and can play it or keep it.
+    DrawnCard,
+    /// The bot has to stack a draw card or take the pending penalty.
+    Stack,
+}
+
+/// A running bot process.
+struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines written by the bot, read on a separate thread so that replies can time out.
+    replies: mpsc::Receiver<String>,
+}
+
+impl BotProcess {
+    fn spawn(program: &str, args: &[&str]) -> Result<Self, String> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Cannot start the bot {program}: {e}"))?;
+        let stdin = child.stdin.take().unwrap(); // Piped above
+        let stdout = child.stdout.take().unwrap(); // Piped above
+        let (sender, replies) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(BotProcess {
+            child,
+            stdin,
+            replies,
+        })
+    }
+
+    /// Sends a request and waits for the reply line.
+    fn ask(&mut self, request: &Request, timeout: Duration) -> Result<String, String> {
+        let request = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        writeln!(self.stdin, "{request}").map_err(|e| e.to_string())?;
+        self.stdin.flush().map_err(|e| e.to_string())?;
+        self.replies
+            .recv_timeout(timeout)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Player controlled by an external program speaking a line-based JSON protocol.
+///
+/// For every move the engine writes a request with the bot's hand, its legal moves and
+/// its observation of the game to the bot's standard input, and the bot writes its
+/// reply to its standard output:
+///
+/// * `{"type": "action", "decision": "turn" | "drawn_card" | "stack", ...}` is answered
+///   with one of the legal actions, e.g. `"Draw"`, `{"Play": 2}` or
+///   `{"PlayWild": [0, "Red"]}`.
+/// * `{"type": "colour", ...}` is answered with a colour, e.g. `"Blue"`.
+///
+/// An illegal or malformed reply is replaced by the move of a Normal AI. A bot that does
+/// not reply in time or exits is not asked again, and the Normal AI plays the rest of
+/// its game. Decisions outside the protocol, such as challenges, are also made by the AI.
+pub struct BotActor {
+    ai: ai::AiActor,
+    process: Option<BotProcess>,
+    timeout: Duration,
+}
+
+impl BotActor {
+    /// Starts the bot with the command line `command`, arguments are separated by spaces.
+    pub fn new(
+        id: usize,
+        command: &str,
+        timeout: Duration,
+        seed: Option<u64>,
+    ) -> Result<BotActor, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("The bot command is empty")?;
+        let args: Vec<&str> = words.collect();
+        Ok(BotActor::from_process(
+            id,
+            BotProcess::spawn(program, &args)?,
+            timeout,
+            seed,
+        ))
+    }
+
+    fn from_process(
+        id: usize,
+        process: BotProcess,
+        timeout: Duration,
+        seed: Option<u64>,
+    ) -> BotActor {
+        BotActor {
+            ai: ai::AiActor::new(id, Box::new(strategy::NormalStrategy), seed),
+            process: Some(process),
+            timeout,
+        }
+    }
+
+    /// Returns the bot's reply, or `None` if the bot did not reply in time.
+    fn ask(&mut self, request: &Request) -> Option<String> {
+        let reply = self.process.as_mut()?.ask(request, self.timeout);
+        if reply.is_err() {
+            self.process = None;
+        }
+        reply.ok()
+    }
+
+    fn ask_action(
+        &mut self,
+        decision: Decision,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> Option<actor::UserAction> {
+        let hand = self.ai.get_player().get_hand().clone();
+        let request = Request::Action {
+            decision,
+            hand: &hand,
+            legal_actions,
+            observation,
+        };
+        let reply = self.ask(&request)?;
+        serde_json::from_str(&reply)
+            .ok()
+            .filter(|action| legal_actions.contains(action))
+    }
+}
+
+impl actor::Actor for BotActor {
+    fn get_id(&self) -> usize {
+        self.ai.get_id()
+    }
+
+    fn get_player(&self) -> &player::Player {
+        self.ai.get_player()
+    }
+
+    fn get_player_mut(&mut self) -> &mut player::Player {
+        self.ai.get_player_mut()
+    }
+
+    fn get_turn_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.ask_action(Decision::Turn, legal_actions, observation)
+            .unwrap_or_else(|| self.ai.get_turn_action(legal_actions, observation))
+    }
+
+    fn get_drawn_card_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.ask_action(Decision::DrawnCard, legal_actions, observation)
+            .unwrap_or_else(|| self.ai.get_drawn_card_action(legal_actions, observation))
+    }
+
+    fn get_stack_action(
+        &mut self,
+        penalty: usize,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.ask_action(Decision::Stack, legal_actions, observation)
+            .unwrap_or_else(|| {
+                self.ai
+                    .get_stack_action(penalty, legal_actions, observation)
+            })
+    }
+
+    fn decide_challenge(&mut self) -> bool {
+        self.ai.decide_challenge()
+    }
+
+    fn catch_missed_uno(&mut self, offender_id: usize) -> bool {
+        self.ai.catch_missed_uno(offender_id)
+    }
+
+    fn decide_jump_in(&mut self, card_index: usize) -> bool {
+        self.ai.decide_jump_in(card_index)
+    }
+
+    fn get_swap_target(&mut self, hand_sizes: &[usize]) -> usize {
+        self.ai.get_swap_target(hand_sizes)
+    }
+
+    fn get_color_choice(&mut self, observation: &observation::Observation) -> card::Colour {
+        let hand = self.ai.get_player().get_hand().clone();
+        let request = Request::Colour {
+            hand: &hand,
+            observation,
+        };
+        self.ask(&request)
+            .and_then(|reply| serde_json::from_str(&reply).ok())
+            .filter(|colour| card::COLOURS.contains(colour))
+            .unwrap_or_else(|| self.ai.get_color_choice(observation))
+    }
+
+    fn pre_turn_action(&mut self) {
+        self.ai.pre_turn_action();
+    }
+
+    fn post_turn_action(&mut self) {
+        self.ai.post_turn_action();
+    }
+
+    fn save_state(&self) -> serde_json::Value {
+        self.ai.save_state()
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<(), String> {
+        self.ai.load_state(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::UserAction;
+    use crate::card::{Card, Colour, Value};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn observe() -> observation::Observation {
+        let top_card = Card {
+            colour: Colour::Red,
+            value: Value::Number(3),
+        };
+        observation::create_test_observation(top_card, &[7])
+    }
+
+    /// Starts a shell script bot that replies `reply` to every request.
+    fn replying_bot(reply: &str, timeout: Duration) -> BotActor {
+        let script = format!("while read line; do echo '{reply}'; done");
+        let process = BotProcess::spawn("sh", &["-c", &script]).unwrap();
+        let mut bot = BotActor::from_process(0, process, timeout, Some(0));
+        bot.get_player_mut().take_card(Card {
+            colour: Colour::Red,
+            value: Value::Number(5),
+        });
+        bot
+    }
+
+    #[test]
+    fn test_request_format() {
+        let request = Request::Action {
+            decision: Decision::DrawnCard,
+            hand: &[],
+            legal_actions: &[UserAction::Play(0), UserAction::Pass],
+            observation: &observe(),
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(json["type"], "action");
+        assert_eq!(json["decision"], "drawn_card");
+        assert_eq!(
+            json["legal_actions"],
+            serde_json::json!([{"Play": 0}, "Pass"])
+        );
+        assert_eq!(json["observation"]["top_card"]["colour"], "Red");
+    }
+
+    #[test]
+    fn test_plays_the_bot_move() {
+        let mut bot = replying_bot("\"Draw\"", TIMEOUT);
+        let legal_actions = [UserAction::Play(0), UserAction::Draw];
+        let action = bot.get_turn_action(&legal_actions, &observe());
+        assert_eq!(action, UserAction::Draw);
+    }
+
+    #[test]
+    fn test_illegal_move_is_replaced() {
+        let mut bot = replying_bot("{\"Play\": 7}", TIMEOUT);
+        let legal_actions = [UserAction::Play(0), UserAction::Draw];
+        let action = bot.get_turn_action(&legal_actions, &observe());
+        assert_eq!(action, UserAction::Play(0));
+        assert!(bot.process.is_some());
+    }
+
+    #[test]
+    fn test_colour_reply() {
+        let mut bot = replying_bot("\"Blue\"", TIMEOUT);
+        assert_eq!(bot.get_color_choice(&observe()), Colour::Blue);
+        let mut bot = replying_bot("\"Wild\"", TIMEOUT);
+        assert_eq!(bot.get_color_choice(&observe()), Colour::Red);
+    }
+
+    #[test]
+    fn test_bot_that_times_out_is_dropped() {
+        let process = BotProcess::spawn("sleep", &["5"]).unwrap();
+        let mut bot = BotActor::from_process(0, process, Duration::from_millis(10), Some(0));
+        let legal_actions = [UserAction::Draw];
+        let action = bot.get_turn_action(&legal_actions, &observe());
+        assert_eq!(action, UserAction::Draw);
+        assert!(bot.process.is_none());
+    }
+
+    #[test]
+    fn test_empty_command_is_rejected() {
+        assert!(BotActor::new(0, " ", TIMEOUT, None).is_err());
+    }
+}
@@ -1,7 +1,8 @@
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
 /// Represents the possible colors of a card.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Colour {
     Red,
     Yellow,
@@ -10,8 +11,16 @@ pub enum Colour {
     Wild,
 }
 
+/// The colours a wild card can be played as.
+pub const COLOURS: [Colour; 4] = [Colour::Red, Colour::Yellow, Colour::Green, Colour::Blue];
+
+/// Returns the position of `colour` in `COLOURS`, or `None` for wild cards.
+pub fn colour_index(colour: Colour) -> Option<usize> {
+    COLOURS.iter().position(|c| *c == colour)
+}
+
 /// Represents the possible values of a card.
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Value {
     Reverse,
     Skip,
@@ -22,18 +31,25 @@ pub enum Value {
 }
 
 /// Represents a card and its attributes.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Card {
     pub colour: Colour,
     pub value: Value,
 }
 
-impl std::fmt::Display for Card {
-    /// Formats the card as a string with colored output.
+impl Card {
+    /// Checks if the card makes the next player draw, i.e. a Draw Two or a Wild Draw.
+    pub fn is_draw_card(&self) -> bool {
+        matches!(self.value, Value::DrawTwo | Value::WildDraw(_))
+    }
+}
+
+impl std::fmt::Display for Colour {
+    /// Formats the colour as a string with colored output.
     ///
     /// Returns a `std::fmt::Result` indicating the success or failure of the formatting operation.
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let colour = match self.colour {
+        let colour = match self {
             Colour::Blue => "Blue".blue(),
             Colour::Red => "Red".red(),
             Colour::Yellow => "Yellow".yellow(),
@@ -47,6 +63,16 @@ impl std::fmt::Display for Card {
             )
             .normal(),
         };
+
+        write!(f, "{colour}")
+    }
+}
+
+impl std::fmt::Display for Card {
+    /// Formats the card as a string with colored output.
+    ///
+    /// Returns a `std::fmt::Result` indicating the success or failure of the formatting operation.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let value = match self.value {
             Value::DrawTwo => "Draw Two".to_string(),
             Value::Reverse => "Reverse".to_string(),
@@ -56,6 +82,6 @@ impl std::fmt::Display for Card {
             Value::WildDraw(n) => format!("Draw +{n}"),
         };
 
-        write!(f, "{colour} {value}")
+        write!(f, "{} {value}", self.colour)
     }
 }
@@ -1,10 +1,16 @@
-use clap::Parser;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use uno::{bot, mcts, rules, score, strategy};
 
 const ABOUT:&str= "Uno is the highly popular card game played by millions around the globe. This game is played by matching and then discarding the cards in one’s hand until none are left.";
 
 #[derive(Parser)]
 #[command(version, about, long_about = ABOUT)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[arg(
         short = 'p',
         long,
@@ -14,12 +20,272 @@ pub struct Args {
     pub num_of_players: usize,
 
     #[arg(
+        global = true,
         short = 'c',
         long,
-        default_value_t = 7,
-        help = "Numbers of cards dealt to each player at the start of the game"
+        help = "Numbers of cards dealt to each player at the start of the game [default: 7]"
+    )]
+    pub num_of_cards: Option<usize>,
+
+    #[arg(
+        global = true,
+        short = 's',
+        long,
+        help = "Seed for shuffling the deck, to replay the exact same game"
+    )]
+    pub seed: Option<u64>,
+
+    #[arg(
+        global = true,
+        long,
+        help = "Allow stacking Draw Two and Wild Draw cards to pass the penalty on"
+    )]
+    pub stacking: bool,
+
+    #[arg(
+        short = 't',
+        long,
+        default_value_t = score::DEFAULT_TARGET_SCORE,
+        help = "Score a player must reach to win the match"
+    )]
+    pub target_score: usize,
+
+    #[arg(
+        global = true,
+        short = 'r',
+        long,
+        help = "TOML file with the house rules to play with"
+    )]
+    pub rules: Option<PathBuf>,
+
+    #[arg(
+        long = "ai",
+        value_parser = difficulty_parser(),
+        value_delimiter = ',',
+        help = "Difficulty of each AI player in seat order, the rest play at normal difficulty"
+    )]
+    pub ai_levels: Vec<strategy::Difficulty>,
+
+    #[arg(
+        global = true,
+        long,
+        default_value_t = mcts::DEFAULT_ITERATIONS,
+        help = "Number of games the MCTS AI simulates for every move"
+    )]
+    pub mcts_iterations: usize,
+
+    #[arg(
+        global = true,
+        long,
+        conflicts_with = "mcts_iterations",
+        help = "Milliseconds the MCTS AI spends simulating games for every move"
+    )]
+    pub mcts_time: Option<u64>,
+
+    #[arg(
+        global = true,
+        long = "bot",
+        help = "Command line of an external bot seated after the AI players, can be repeated"
+    )]
+    pub bots: Vec<String>,
+
+    #[arg(
+        global = true,
+        long,
+        default_value_t = bot::DEFAULT_TIMEOUT_MS,
+        help = "Milliseconds an external bot has to reply before the AI takes over"
+    )]
+    pub bot_timeout: u64,
+
+    #[arg(
+        short = 'v',
+        long,
+        help = "Print the reason for every move the AI players make"
+    )]
+    pub verbose: bool,
+
+    #[arg(
+        long,
+        help = "Practice against the AI players, with an 'undo' command to take turns back"
+    )]
+    pub practice: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Resume a match saved with the 'save' command, the players, rules and practice mode are taken from the file"
     )]
-    pub num_of_cards: usize,
+    pub load: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "File the match is recorded to, to watch it again with the replay command, a new file named after the current time by default"
+    )]
+    pub record: Option<PathBuf>,
+
+    #[arg(long, conflicts_with = "record", help = "Do not record the match")]
+    pub no_record: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Play a batch of games between AI players without a human player
+    Simulate(SimulateArgs),
+    /// Rank AI strategies by playing them against each other in every seating order
+    Tournament(TournamentArgs),
+    /// Step through a recorded match turn by turn, showing every hand
+    Replay(ReplayArgs),
+}
+
+#[derive(clap::Args)]
+pub struct SimulateArgs {
+    #[arg(
+        short = 'n',
+        long,
+        default_value_t = 1000,
+        help = "Number of games to play"
+    )]
+    pub games: usize,
+
+    #[arg(
+        short = 'j',
+        long,
+        default_value_t = 1,
+        help = "Number of threads the games are split across"
+    )]
+    pub threads: usize,
+
+    #[arg(
+        long = "seats",
+        value_parser = difficulty_parser(),
+        value_delimiter = ',',
+        default_value = "normal,normal",
+        help = "Difficulty of the AI player in each seat"
+    )]
+    pub ai_levels: Vec<strategy::Difficulty>,
+}
+
+#[derive(clap::Args)]
+pub struct TournamentArgs {
+    #[arg(
+        long,
+        value_parser = difficulty_parser(),
+        value_delimiter = ',',
+        required = true,
+        help = "AI strategies taking part, each of them sits at the table in every game"
+    )]
+    pub strategies: Vec<strategy::Difficulty>,
+
+    #[arg(
+        short = 'n',
+        long,
+        default_value_t = 100,
+        help = "Number of games played for every seating order"
+    )]
+    pub games: usize,
+
+    #[arg(
+        short = 'j',
+        long,
+        default_value_t = 1,
+        help = "Number of threads the games are split across"
+    )]
+    pub threads: usize,
+}
+
+#[derive(clap::Args)]
+pub struct ReplayArgs {
+    #[arg(
+        value_name = "FILE",
+        help = "Replay file written while playing a match"
+    )]
+    pub file: PathBuf,
+}
+
+impl Args {
+    /// Builds the rules of the game from the rules file, if any, and the command line
+    /// options, which take precedence over the file.
+    pub fn get_rules(&self) -> Result<rules::Rules, String> {
+        let mut rules = match &self.rules {
+            Some(path) => rules::Rules::from_file(path)?,
+            None => rules::Rules::default(),
+        };
+        if let Some(num_of_cards) = self.num_of_cards {
+            rules.starting_hand_size = num_of_cards;
+        }
+        rules.stacking |= self.stacking;
+        Ok(rules)
+    }
+
+    /// Returns the difficulty of every AI player, one per seat after the human player
+    /// that is not taken by a bot.
+    pub fn get_ai_levels(&self) -> Result<Vec<strategy::Difficulty>, String> {
+        let num_of_ai_players = self.num_of_players.saturating_sub(1);
+        if self.bots.len() > num_of_ai_players {
+            return Err(format!(
+                "Too many bots, the number of players other than the human is {num_of_ai_players}"
+            ));
+        }
+        let num_of_ai_players = num_of_ai_players - self.bots.len();
+        if self.ai_levels.len() > num_of_ai_players {
+            return Err(format!(
+                "Too many AI difficulties, the number of AI players is {num_of_ai_players}"
+            ));
+        }
+        let mut ai_levels = self.ai_levels.clone();
+        ai_levels.resize(num_of_ai_players, strategy::Difficulty::Normal);
+        Ok(ai_levels)
+    }
+
+    /// Returns the file the match is recorded to: the one given with `--record`, which
+    /// must not exist yet, a new file named after the current time, or `None` with
+    /// `--no-record`.
+    pub fn get_replay_file(&self) -> Result<Option<PathBuf>, String> {
+        match &self.record {
+            _ if self.no_record => Ok(None),
+            Some(path) if path.exists() => Err(format!(
+                "The replay file {} already exists, record the match to a new file",
+                path.display()
+            )),
+            Some(path) => Ok(Some(path.clone())),
+            None => Ok(Some(new_replay_file())),
+        }
+    }
+
+    pub fn get_bot_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.bot_timeout)
+    }
+
+    /// Returns how much the MCTS AI searches before every move.
+    pub fn get_search_budget(&self) -> mcts::SearchBudget {
+        match self.mcts_time {
+            Some(millis) => mcts::SearchBudget::Time(std::time::Duration::from_millis(millis)),
+            None => mcts::SearchBudget::Iterations(self.mcts_iterations),
+        }
+    }
+}
+
+/// Names a replay file after the current time, numbering it if a match was already
+/// recorded in the same second.
+fn new_replay_file() -> PathBuf {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let stem = format!("uno-replay-{secs}");
+    let mut path = PathBuf::from(format!("{stem}.json"));
+    let mut number = 1;
+    while path.exists() {
+        path = PathBuf::from(format!("{stem}-{number}.json"));
+        number += 1;
+    }
+    path
+}
+
+/// Parses the name of a difficulty, listing the names in the help and in the errors.
+fn difficulty_parser() -> impl TypedValueParser<Value = strategy::Difficulty> {
+    PossibleValuesParser::new(strategy::Difficulty::ALL.map(strategy::Difficulty::name))
+        .map(|name| name.parse().unwrap()) // Only the names of difficulties get through
 }
 
 pub fn parse_input() -> Args {
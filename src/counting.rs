@@ -0,0 +1,419 @@
+use crate::default_deck::GAME_DECK;
+use crate::{actor, card, observation, strategy};
+use rand_chacha::ChaCha8Rng;
+use std::borrow::Cow;
+
+/// Strategy that counts the cards seen so far.
+///
+/// It keeps track of which cards are still unseen against the composition of the
+/// full deck, and of the colours the next player is likely out of because they drew
+/// instead of following them. It uses both to pick the colour to declare and the card
+/// to play, steering the colour in play to one the next player cannot follow.
+pub struct CountingStrategy;
+
+/// What the AI has worked out from the cards seen so far.
+struct Knowledge {
+    /// Number of unseen cards of each colour, in the order of `card::COLOURS`.
+    unseen: [usize; 4],
+    /// Colours the next player is likely out of, in the order of `card::COLOURS`.
+    next_is_missing: [bool; 4],
+}
+
+impl Knowledge {
+    fn new(hand: &[card::Card], observation: &observation::Observation) -> Self {
+        let mut unseen = [0usize; 4];
+        for card in GAME_DECK.iter() {
+            if let Some(i) = card::colour_index(card.colour) {
+                unseen[i] += 1;
+            }
+        }
+        for card in hand.iter().chain(observation.discard_pile.iter()) {
+            if let Some(i) = card::colour_index(card.colour) {
+                unseen[i] = unseen[i].saturating_sub(1);
+            }
+        }
+
+        let next_is_missing = match observation.opponent_ids.first() {
+            Some(next) => missing_colours(*next, &observation.history),
+            None => [false; 4],
+        };
+        Knowledge {
+            unseen,
+            next_is_missing,
+        }
+    }
+
+    /// Rates how hard it is for the next player to follow `colour`.
+    fn colour_score(&self, colour: card::Colour) -> f64 {
+        let Some(i) = card::colour_index(colour) else {
+            return 0.0;
+        };
+        let mut score = 1.0 - self.unseen[i] as f64 / 25.0;
+        if self.next_is_missing[i] {
+            score += 2.0;
+        }
+        score
+    }
+
+    fn best_colour(&self, hand: &[card::Card]) -> card::Colour {
+        let score = |colour: &card::Colour| {
+            let in_hand = hand.iter().filter(|card| card.colour == *colour).count();
+            in_hand as f64 + self.colour_score(*colour)
+        };
+        *card::COLOURS
+            .iter()
+            .max_by(|a, b| score(a).total_cmp(&score(b)))
+            .unwrap() // The iterator is never empty
+    }
+}
+
+/// Works out the colours `player` is likely out of from the play history.
+///
+/// A player who draws a card on their turn instead of playing is assumed to be out
+/// of the colour in play, until they play that colour, take a penalty or get another
+/// hand in a swap.
+fn missing_colours(player: usize, history: &[observation::PlayRecord]) -> [bool; 4] {
+    let mut missing = [false; 4];
+    let mut colour_in_play = None;
+    let mut is_penalty_pending = false;
+    // Player taking a penalty and the number of cards they still have to draw
+    let mut penalty_draws = (0, 0);
+    let mut previous: Option<&observation::PlayRecord> = None;
+    for record in history {
+        match record {
+            observation::PlayRecord::CardPlayed { player: p, card } => {
+                colour_in_play = card::colour_index(card.colour);
+                is_penalty_pending = card.is_draw_card();
+                penalty_draws = (0, 0);
+                if *p == player {
+                    if let Some(i) = colour_in_play {
+                        missing[i] = false;
+                    }
+                }
+            }
+            observation::PlayRecord::ColourDeclared { colour, .. } => {
+                colour_in_play = card::colour_index(*colour);
+            }
+            observation::PlayRecord::CardDrawn { player: p } => {
+                let is_drawing_again = matches!(
+                    previous,
+                    Some(observation::PlayRecord::CardDrawn { player: q }) if q == p
+                );
+                if penalty_draws.0 == *p && penalty_draws.1 > 0 {
+                    penalty_draws.1 -= 1;
+                } else if is_penalty_pending && !is_drawing_again {
+                    // The first player to draw after a draw card takes the penalty
+                    is_penalty_pending = false;
+                    if *p == player {
+                        missing = [false; 4];
+                    }
+                } else if *p == player && !is_drawing_again {
+                    if let Some(i) = colour_in_play {
+                        missing[i] = true;
+                    }
+                }
+            }
+            observation::PlayRecord::PenaltyApplied {
+                player: p,
+                num_of_cards,
+            } => {
+                is_penalty_pending = false;
+                penalty_draws = (*p, *num_of_cards);
+                if *p == player {
+                    missing = [false; 4];
+                }
+            }
+            observation::PlayRecord::HandsSwapped { player: p, other } => {
+                if *p == player || *other == player {
+                    missing = [false; 4];
+                }
+            }
+            observation::PlayRecord::HandsRotated => missing = [false; 4],
+        }
+        previous = Some(record);
+    }
+    missing
+}
+
+impl CountingStrategy {
+    /// Ranks a playable card, the highest ranked card is played.
+    /// Skip and Draw Two cards are saved for a next player who leads or is close to winning.
+    fn rank(
+        card: &card::Card,
+        colour: card::Colour,
+        knowledge: &Knowledge,
+        observation: &observation::Observation,
+    ) -> f64 {
+        let next_hand_size = observation.next_hand_size().unwrap_or(usize::MAX);
+        let next_is_leader = observation.next_is_leader();
+        let base = match card.value {
+            card::Value::Number(n) => n as f64 / 10.0,
+            card::Value::Skip | card::Value::DrawTwo if next_hand_size <= 3 || next_is_leader => {
+                3.0
+            }
+            card::Value::Skip | card::Value::DrawTwo | card::Value::Reverse => 0.05,
+            card::Value::WildDraw(_) if next_hand_size <= 2 => 3.0,
+            card::Value::Wild | card::Value::WildDraw(_) => -1.0,
+        };
+        base + knowledge.colour_score(colour)
+    }
+
+    /// Tells why `card`, played as `colour`, has the highest rank.
+    fn reason(
+        card: &card::Card,
+        colour: card::Colour,
+        knowledge: &Knowledge,
+        observation: &observation::Observation,
+    ) -> Cow<'static, str> {
+        let next_hand_size = observation.next_hand_size().unwrap_or(usize::MAX);
+        let next_is_leader = observation.next_is_leader();
+        let holds_back_next = match card.value {
+            card::Value::Skip | card::Value::DrawTwo => next_hand_size <= 3 || next_is_leader,
+            card::Value::WildDraw(_) => next_hand_size <= 2,
+            _ => false,
+        };
+        if holds_back_next {
+            return Cow::Owned(format!(
+                "it holds back the next player, who has {next_hand_size} cards"
+            ));
+        }
+        match card::colour_index(colour) {
+            Some(i) if knowledge.next_is_missing[i] => Cow::Owned(format!(
+                "it turns play to {colour}, which the next player seems to be out of"
+            )),
+            Some(i) => Cow::Owned(format!(
+                "it leaves {colour} in play, with {} cards of it unseen",
+                knowledge.unseen[i]
+            )),
+            None => Cow::Borrowed("it is the best of the playable cards"),
+        }
+    }
+}
+
+impl strategy::Strategy for CountingStrategy {
+    fn choose_action(
+        &mut self,
+        hand: &[card::Card],
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+        _rng: &mut ChaCha8Rng,
+    ) -> strategy::Choice {
+        let knowledge = Knowledge::new(hand, observation);
+        let wild_colour = knowledge.best_colour(hand);
+        let best = legal_actions
+            .iter()
+            .filter_map(|action| match action {
+                actor::UserAction::Play(i) => Some((*i, *action)),
+                actor::UserAction::PlayWild(i, c) if *c == wild_colour => Some((*i, *action)),
+                _ => None,
+            })
+            .filter_map(|(i, action)| {
+                let card = hand.get(i)?;
+                let colour = match action {
+                    actor::UserAction::PlayWild(_, c) => c,
+                    _ => card.colour,
+                };
+                let rank = CountingStrategy::rank(card, colour, &knowledge, observation);
+                Some((rank, card, colour, action))
+            })
+            .rev() // Prefer the first card when ranks are equal
+            .max_by(|(a, ..), (b, ..)| a.total_cmp(b));
+        match best {
+            Some((_, card, colour, action)) => strategy::Choice::new(
+                Some(action),
+                CountingStrategy::reason(card, colour, &knowledge, observation),
+            ),
+            None => strategy::Choice::new(None, "no card can be played"),
+        }
+    }
+
+    fn choose_colour(
+        &mut self,
+        hand: &[card::Card],
+        observation: &observation::Observation,
+        _rng: &mut ChaCha8Rng,
+    ) -> card::Colour {
+        Knowledge::new(hand, observation).best_colour(hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::UserAction;
+    use crate::card::{Card, Colour, Value};
+    use crate::deck;
+    use crate::observation::PlayRecord;
+    use crate::strategy::Strategy;
+    use std::rc::Rc;
+
+    fn card(colour: Colour, value: Value) -> Card {
+        Card { colour, value }
+    }
+
+    fn observe(discard_pile: Vec<Card>, history: Vec<PlayRecord>) -> observation::Observation {
+        let top_card = *discard_pile.last().unwrap();
+        observation::Observation {
+            discard_pile: Rc::new(discard_pile),
+            history: Rc::new(history),
+            ..observation::create_test_observation(top_card, &[5])
+        }
+    }
+
+    fn play(player: usize, card: Card) -> PlayRecord {
+        PlayRecord::CardPlayed { player, card }
+    }
+
+    fn draw(player: usize) -> PlayRecord {
+        PlayRecord::CardDrawn { player }
+    }
+
+    #[test]
+    fn test_drawing_on_a_colour_marks_it_missing() {
+        let history = vec![play(0, card(Colour::Red, Value::Number(3))), draw(1)];
+        assert_eq!(missing_colours(1, &history), [true, false, false, false]);
+    }
+
+    #[test]
+    fn test_playing_a_colour_clears_it() {
+        let history = vec![
+            play(0, card(Colour::Red, Value::Number(3))),
+            draw(1),
+            play(0, card(Colour::Red, Value::Number(4))),
+            play(1, card(Colour::Red, Value::Number(5))),
+        ];
+        assert_eq!(missing_colours(1, &history), [false; 4]);
+    }
+
+    #[test]
+    fn test_penalty_draws_do_not_mark_colours() {
+        let history = vec![play(0, card(Colour::Red, Value::DrawTwo)), draw(1), draw(1)];
+        assert_eq!(missing_colours(1, &history), [false; 4]);
+    }
+
+    #[test]
+    fn test_uno_penalty_resets_missing_colours() {
+        let history = vec![
+            play(0, card(Colour::Red, Value::Number(3))),
+            draw(1),
+            play(0, card(Colour::Blue, Value::Number(3))),
+            PlayRecord::PenaltyApplied {
+                player: 1,
+                num_of_cards: 2,
+            },
+            draw(1),
+            draw(1),
+        ];
+        assert_eq!(missing_colours(1, &history), [false; 4]);
+    }
+
+    #[test]
+    fn test_hand_swaps_reset_missing_colours() {
+        let history = vec![play(0, card(Colour::Red, Value::Number(3))), draw(1)];
+        let swapped = |record: PlayRecord| {
+            let mut history = history.clone();
+            history.push(record);
+            missing_colours(1, &history)
+        };
+        assert_eq!(
+            swapped(PlayRecord::HandsSwapped {
+                player: 0,
+                other: 2
+            }),
+            [true, false, false, false]
+        );
+        assert_eq!(
+            swapped(PlayRecord::HandsSwapped {
+                player: 2,
+                other: 1
+            }),
+            [false; 4]
+        );
+        assert_eq!(swapped(PlayRecord::HandsRotated), [false; 4]);
+    }
+
+    #[test]
+    fn test_declared_colour_is_the_colour_in_play() {
+        let history = vec![
+            play(0, card(Colour::Wild, Value::Wild)),
+            PlayRecord::ColourDeclared {
+                player: 0,
+                colour: Colour::Blue,
+            },
+            draw(1),
+        ];
+        assert_eq!(missing_colours(1, &history), [false, false, false, true]);
+    }
+
+    #[test]
+    fn test_unseen_cards_exclude_hand_and_discard_pile() {
+        let hand = [card(Colour::Green, Value::Number(1))];
+        let discard_pile = vec![
+            card(Colour::Green, Value::Number(2)),
+            card(Colour::Red, Value::Number(2)),
+        ];
+        let knowledge = Knowledge::new(&hand, &observe(discard_pile, Vec::new()));
+        assert_eq!(knowledge.unseen, [24, 25, 23, 25]);
+    }
+
+    #[test]
+    fn test_chooses_a_colour_the_next_player_is_missing() {
+        let hand = [
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Blue, Value::Number(1)),
+        ];
+        let top_card = card(Colour::Blue, Value::Number(3));
+        let observation = observe(vec![top_card], vec![play(0, top_card), draw(1)]);
+        let mut rng = deck::new_rng(Some(0));
+        let colour = CountingStrategy.choose_colour(&hand, &observation, &mut rng);
+        assert_eq!(colour, Colour::Blue);
+    }
+
+    #[test]
+    fn test_plays_the_colour_the_next_player_is_missing() {
+        let hand = [
+            card(Colour::Red, Value::Number(9)),
+            card(Colour::Green, Value::Number(3)),
+        ];
+        let top_card = card(Colour::Green, Value::Number(9));
+        let history = vec![
+            play(0, card(Colour::Green, Value::Number(5))),
+            draw(1),
+            play(0, top_card),
+        ];
+        let observation = observe(vec![top_card], history);
+        let legal_actions = [UserAction::Play(0), UserAction::Play(1), UserAction::Draw];
+        let mut rng = deck::new_rng(Some(0));
+        let choice = CountingStrategy.choose_action(&hand, &legal_actions, &observation, &mut rng);
+        assert_eq!(choice.action, Some(UserAction::Play(1)));
+        assert_eq!(
+            choice.reason,
+            "it turns play to Green, which the next player seems to be out of"
+        );
+    }
+
+    #[test]
+    fn test_saves_draw_two_for_the_leader() {
+        let hand = [
+            card(Colour::Red, Value::DrawTwo),
+            card(Colour::Red, Value::Number(5)),
+        ];
+        let top_card = card(Colour::Red, Value::Number(1));
+        let mut observation = observe(vec![top_card], Vec::new());
+        observation.opponent_ids = vec![1, 2];
+        observation.opponent_hand_sizes = vec![5, 4];
+        let legal_actions = [UserAction::Play(0), UserAction::Play(1)];
+        let mut rng = deck::new_rng(Some(0));
+        let action = CountingStrategy
+            .choose_action(&hand, &legal_actions, &observation, &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::Play(1)));
+        observation.opponent_hand_sizes = vec![2, 4];
+        let choice = CountingStrategy.choose_action(&hand, &legal_actions, &observation, &mut rng);
+        assert_eq!(choice.action, Some(UserAction::Play(0)));
+        assert_eq!(
+            choice.reason,
+            "it holds back the next player, who has 2 cards"
+        );
+    }
+}
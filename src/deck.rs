@@ -1,8 +1,11 @@
 use crate::card;
 use crate::default_deck::GAME_DECK;
 use mockall::automock;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use std::rc::Rc;
 
 /// Type alias for results returned by deck operations.
 type DeckResult<T> = Result<T, Error>;
@@ -15,9 +18,14 @@ pub enum Error {
 }
 
 /// Struct representing a deck of cards with draw and discard piles.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Deck {
     draw_pile: VecDeque<card::Card>,
-    discard_pile: VecDeque<card::Card>,
+    /// Shared with the observations, so that observing the game does not copy it.
+    discard_pile: Rc<Vec<card::Card>>,
+    /// Colour chosen for the wild card on top of the discard pile, if any.
+    declared_colour: Option<card::Colour>,
+    rng: ChaCha8Rng,
 }
 
 #[automock]
@@ -32,6 +40,7 @@ pub trait DeckTrait {
 
     /// Gets the top card from the discard pile.
     /// The function returns a reference to the top card if successful, otherwise an error.
+    #[allow(clippy::needless_lifetimes)] // mockall needs the explicit lifetime
     fn get_top_card<'a>(&'a self) -> DeckResult<&'a card::Card>;
 
     /// Refills the draw pile from the discard pile.
@@ -41,11 +50,28 @@ pub trait DeckTrait {
     /// Returns the number of cards in the draw pile.
     fn number_of_cards_in_draw_pile(&self) -> usize;
 
-    /// Changes the color of the top card in the discard pile.
-    fn change_colour_of_top_card_in_discard(&mut self, colour: &card::Colour);
+    /// Returns the cards in the discard pile, from the bottom to the top. The pile is
+    /// only copied if it changes while the returned one is still alive.
+    fn get_discard_pile(&self) -> Rc<Vec<card::Card>>;
+
+    /// Gets the colour in play: the colour declared for the top card if it is a wild card,
+    /// otherwise the colour of the top card itself.
+    /// The function returns the colour if successful, otherwise an error.
+    fn get_current_colour(&self) -> DeckResult<card::Colour>;
+
+    /// Declares the colour in play for the top card in the discard pile.
+    /// The card itself keeps its original colour, so wild cards remain wild.
+    fn declare_colour(&mut self, colour: &card::Colour);
+
+    /// Returns the top card of the discard pile to the draw pile, reshuffles it and
+    /// turns over a new card. Used when the starting card cannot be played on.
+    /// The function returns `Ok(())` if successful, otherwise an error.
+    fn replace_top_card(&mut self) -> DeckResult<()>;
 
     /// Creates a new deck, optionally using a provided set of cards.
-    fn new(deck: Option<Vec<card::Card>>) -> Self;
+    /// Every shuffle is driven by an RNG seeded with `seed`, so the same seed
+    /// always yields the same card order. A random seed is used if `None`.
+    fn new(deck: Option<Vec<card::Card>>, seed: Option<u64>) -> Self;
 }
 
 impl DeckTrait for Deck {
@@ -57,23 +83,22 @@ impl DeckTrait for Deck {
     }
 
     fn discard(&mut self, card: card::Card) {
-        self.discard_pile.push_back(card);
+        Rc::make_mut(&mut self.discard_pile).push(card);
+        self.declared_colour = None;
     }
 
     fn refill_draw_pile(&mut self) -> DeckResult<()> {
-        if let Some(card) = self.discard_pile.pop_back() {
-            self.revert_wild_cards_in_discard_pile();
-            self.draw_pile.append(&mut self.discard_pile);
-            self.discard_pile.clear();
+        if let Some((card, discarded)) = self.discard_pile.split_last() {
+            self.draw_pile.extend(discarded);
+            self.discard_pile = Rc::new(vec![*card]);
             self.shuffle();
-            self.discard(card);
             return Ok(());
         }
         Err(Error::DiscardPileIsEmpty)
     }
 
-    fn get_top_card<'a>(&'a self) -> DeckResult<&'a card::Card> {
-        if let Some(c) = self.discard_pile.back() {
+    fn get_top_card(&self) -> DeckResult<&card::Card> {
+        if let Some(c) = self.discard_pile.last() {
             return Ok(c);
         }
         Err(Error::DiscardPileIsEmpty)
@@ -83,13 +108,32 @@ impl DeckTrait for Deck {
         self.draw_pile.len()
     }
 
-    fn change_colour_of_top_card_in_discard(&mut self, colour: &card::Colour) {
-        if let Some(c) = self.discard_pile.back_mut() {
-            c.colour = *colour;
+    fn get_discard_pile(&self) -> Rc<Vec<card::Card>> {
+        Rc::clone(&self.discard_pile)
+    }
+
+    fn get_current_colour(&self) -> DeckResult<card::Colour> {
+        let card = self.get_top_card()?;
+        Ok(self.declared_colour.unwrap_or(card.colour))
+    }
+
+    fn declare_colour(&mut self, colour: &card::Colour) {
+        if !self.discard_pile.is_empty() {
+            self.declared_colour = Some(*colour);
         }
     }
 
-    fn new(deck: Option<Vec<card::Card>>) -> Self {
+    fn replace_top_card(&mut self) -> DeckResult<()> {
+        if let Some(card) = Rc::make_mut(&mut self.discard_pile).pop() {
+            self.draw_pile.push_back(card);
+            self.shuffle();
+            self.declared_colour = None;
+            return self.discard_from_draw_pile();
+        }
+        Err(Error::DiscardPileIsEmpty)
+    }
+
+    fn new(deck: Option<Vec<card::Card>>, seed: Option<u64>) -> Self {
         let card_set = if let Some(deck) = deck {
             deck
         } else {
@@ -97,7 +141,9 @@ impl DeckTrait for Deck {
         };
         let mut deck = Deck {
             draw_pile: VecDeque::from(card_set),
-            discard_pile: VecDeque::new(),
+            discard_pile: Rc::default(),
+            declared_colour: None,
+            rng: new_rng(seed),
         };
         deck.shuffle();
         assert!(
@@ -109,23 +155,35 @@ impl DeckTrait for Deck {
 }
 
 impl Deck {
-    /// Shuffles the draw pile randomly.
+    /// Creates a deck with the given piles, in the order the cards are drawn and
+    /// from the bottom to the top of the discard pile. Nothing is shuffled until
+    /// the draw pile has to be refilled.
+    pub fn from_piles(
+        draw_pile: Vec<card::Card>,
+        discard_pile: Vec<card::Card>,
+        declared_colour: Option<card::Colour>,
+        seed: Option<u64>,
+    ) -> Self {
+        Deck {
+            draw_pile: VecDeque::from(draw_pile),
+            discard_pile: Rc::new(discard_pile),
+            declared_colour,
+            rng: new_rng(seed),
+        }
+    }
+
+    /// Iterates over the discard pile from the bottom to the top.
+    pub fn discarded_cards(&self) -> impl Iterator<Item = &card::Card> {
+        self.discard_pile.iter()
+    }
+
+    /// Shuffles the draw pile using the deck's RNG.
     fn shuffle(&mut self) {
-        let mut rng = thread_rng();
         let mut card_vec: Vec<card::Card> = self.draw_pile.drain(..).collect();
-        card_vec.shuffle(&mut rng);
+        card_vec.shuffle(&mut self.rng);
         self.draw_pile = VecDeque::from(card_vec);
     }
 
-    /// Reverts wild cards in the discard pile to their original color.
-    fn revert_wild_cards_in_discard_pile(&mut self) {
-        let _ = self
-            .discard_pile
-            .iter_mut()
-            .filter(|card| matches!(card.value, card::Value::Wild | card::Value::WildDraw(_)))
-            .collect::<Vec<_>>();
-    }
-
     /// Discards a card from the draw pile to the discard pile.
     fn discard_from_draw_pile(&mut self) -> DeckResult<()> {
         if let Some(card) = self.draw_pile.pop_front() {
@@ -137,6 +195,15 @@ impl Deck {
     }
 }
 
+/// Creates the RNG used for shuffling, seeded with `seed` if provided or from
+/// system entropy otherwise.
+pub fn new_rng(seed: Option<u64>) -> ChaCha8Rng {
+    match seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,7 +212,9 @@ mod tests {
     fn create_test_deck() -> Deck {
         Deck {
             draw_pile: VecDeque::new(),
-            discard_pile: VecDeque::new(),
+            discard_pile: Rc::default(),
+            declared_colour: None,
+            rng: new_rng(Some(0)),
         }
     }
 
@@ -175,7 +244,7 @@ mod tests {
             value: Value::Skip,
         };
         deck.discard(card);
-        assert_eq!(deck.discard_pile.back().unwrap(), &card);
+        assert_eq!(deck.discard_pile.last().unwrap(), &card);
     }
 
     #[test]
@@ -194,7 +263,7 @@ mod tests {
         deck.refill_draw_pile().unwrap();
         assert_eq!(deck.draw_pile.len(), 1);
         assert_eq!(deck.discard_pile.len(), 1);
-        assert_eq!(deck.discard_pile.back().unwrap(), &card2);
+        assert_eq!(deck.discard_pile.last().unwrap(), &card2);
     }
 
     #[test]
@@ -208,7 +277,7 @@ mod tests {
         deck.refill_draw_pile().unwrap();
         assert_eq!(deck.draw_pile.len(), 0);
         assert_eq!(deck.discard_pile.len(), 1);
-        assert_eq!(deck.discard_pile.back().unwrap(), &card);
+        assert_eq!(deck.discard_pile.last().unwrap(), &card);
     }
 
     #[test]
@@ -250,20 +319,129 @@ mod tests {
     }
 
     #[test]
-    fn test_change_colour_of_top_card_in_discard() {
+    fn test_declare_colour() {
+        let mut deck = create_test_deck();
+        let card = Card {
+            colour: Colour::Wild,
+            value: Value::Wild,
+        };
+        deck.discard(card);
+        deck.declare_colour(&Colour::Blue);
+        assert_eq!(deck.discard_pile.last().unwrap(), &card);
+        assert_eq!(deck.get_current_colour(), Ok(Colour::Blue));
+    }
+
+    #[test]
+    fn test_get_current_colour_without_declared_colour() {
+        let mut deck = create_test_deck();
+        deck.discard(Card {
+            colour: Colour::Green,
+            value: Value::Number(3),
+        });
+        assert_eq!(deck.get_current_colour(), Ok(Colour::Green));
+    }
+
+    #[test]
+    fn test_get_current_colour_empty_discard_pile() {
+        let mut deck = create_test_deck();
+        deck.declare_colour(&Colour::Blue);
+        assert_eq!(
+            deck.get_current_colour().unwrap_err(),
+            Error::DiscardPileIsEmpty
+        );
+    }
+
+    #[test]
+    fn test_discard_clears_declared_colour() {
+        let mut deck = create_test_deck();
+        deck.discard(Card {
+            colour: Colour::Wild,
+            value: Value::Wild,
+        });
+        deck.declare_colour(&Colour::Blue);
+        deck.discard(Card {
+            colour: Colour::Red,
+            value: Value::Wild,
+        });
+        assert_eq!(deck.get_current_colour(), Ok(Colour::Red));
+    }
+
+    #[test]
+    fn test_refill_draw_pile_keeps_declared_colour() {
         let mut deck = create_test_deck();
         let card = Card {
+            colour: Colour::Wild,
+            value: Value::WildDraw(4),
+        };
+        deck.discard(Card {
+            colour: Colour::Red,
+            value: Value::Number(2),
+        });
+        deck.discard(card);
+        deck.declare_colour(&Colour::Yellow);
+        deck.refill_draw_pile().unwrap();
+        assert_eq!(deck.discard_pile.last().unwrap(), &card);
+        assert_eq!(deck.get_current_colour(), Ok(Colour::Yellow));
+    }
+
+    #[test]
+    fn test_refill_draw_pile_recycles_true_wild_cards() {
+        let mut deck = create_test_deck();
+        let wild = Card {
             colour: Colour::Wild,
             value: Value::Wild,
         };
+        let wild_draw = Card {
+            colour: Colour::Wild,
+            value: Value::WildDraw(4),
+        };
+        deck.discard(wild);
+        deck.declare_colour(&Colour::Blue);
+        deck.discard(wild_draw);
+        deck.declare_colour(&Colour::Green);
+        deck.discard(Card {
+            colour: Colour::Green,
+            value: Value::Number(7),
+        });
+        deck.refill_draw_pile().unwrap();
+        assert!(deck.draw_pile.contains(&wild));
+        assert!(deck.draw_pile.contains(&wild_draw));
+        assert!(deck
+            .draw_pile
+            .iter()
+            .all(|c| c.value != Value::Wild || c.colour == Colour::Wild));
+    }
+
+    #[test]
+    fn test_replace_top_card_success() {
+        let mut deck = create_test_deck();
+        let card = Card {
+            colour: Colour::Wild,
+            value: Value::WildDraw(4),
+        };
+        let other_card = Card {
+            colour: Colour::Red,
+            value: Value::Number(5),
+        };
+        deck.draw_pile.push_back(other_card);
         deck.discard(card);
-        deck.change_colour_of_top_card_in_discard(&Colour::Blue);
-        assert_eq!(deck.discard_pile.back().unwrap().colour, Colour::Blue);
+        deck.replace_top_card().unwrap();
+        assert_eq!(deck.draw_pile.len(), 1);
+        assert_eq!(deck.discard_pile.len(), 1);
+    }
+
+    #[test]
+    fn test_replace_top_card_empty_discard_pile() {
+        let mut deck = create_test_deck();
+        assert_eq!(
+            deck.replace_top_card().unwrap_err(),
+            Error::DiscardPileIsEmpty
+        );
     }
 
     #[test]
     fn test_new_deck_default() {
-        let deck = Deck::new(None);
+        let deck = Deck::new(None, None);
         assert!(!deck.draw_pile.is_empty());
         assert_eq!(deck.discard_pile.len(), 1);
     }
@@ -280,7 +458,7 @@ mod tests {
                 value: Value::Number(2),
             },
         ];
-        let deck = Deck::new(Some(card_set.to_vec()));
+        let deck = Deck::new(Some(card_set.to_vec()), None);
         assert_eq!(deck.draw_pile.len(), card_set.len() - 1);
         assert_eq!(deck.discard_pile.len(), 1);
     }
@@ -288,7 +466,7 @@ mod tests {
     #[test]
     #[should_panic]
     fn test_new_deck_fail() {
-        let _ = Deck::new(Some(Vec::new()));
+        let _ = Deck::new(Some(Vec::new()), None);
     }
 
     #[test]
@@ -310,15 +488,32 @@ mod tests {
     }
 
     #[test]
-    fn test_rever_wild_cards_in_discard_pile() {
-        let mut deck = create_test_deck();
-        let card = Card {
-            colour: Colour::Blue,
-            value: Value::Wild,
-        };
-        deck.discard(card);
-        deck.revert_wild_cards_in_discard_pile();
-        assert_eq!(*deck.discard_pile.back().unwrap(), card);
+    fn test_new_deck_same_seed_same_order() {
+        let deck1 = Deck::new(None, Some(42));
+        let deck2 = Deck::new(None, Some(42));
+        assert_eq!(deck1.draw_pile, deck2.draw_pile);
+        assert_eq!(deck1.discard_pile, deck2.discard_pile);
+    }
+
+    #[test]
+    fn test_new_deck_different_seed_different_order() {
+        let deck1 = Deck::new(None, Some(1));
+        let deck2 = Deck::new(None, Some(2));
+        assert_ne!(deck1.draw_pile, deck2.draw_pile);
+    }
+
+    #[test]
+    fn test_refill_draw_pile_same_seed_same_order() {
+        let mut deck1 = Deck::new(None, Some(7));
+        let mut deck2 = Deck::new(None, Some(7));
+        for deck in [&mut deck1, &mut deck2] {
+            while let Ok(card) = deck.draw() {
+                deck.discard(card);
+            }
+            deck.refill_draw_pile().unwrap();
+        }
+        assert_eq!(deck1.draw_pile, deck2.draw_pile);
+        assert_eq!(deck1.discard_pile, deck2.discard_pile);
     }
 
     #[test]
@@ -330,7 +525,7 @@ mod tests {
         };
         deck.draw_pile.push_back(card);
         deck.discard_from_draw_pile().unwrap();
-        assert_eq!(deck.discard_pile.back().unwrap(), &card);
+        assert_eq!(deck.discard_pile.last().unwrap(), &card);
     }
 
     #[test]
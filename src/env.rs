@@ -0,0 +1,481 @@
+use crate::flow::{GameFlow, GameState};
+use crate::{actor, ai, card, game, mcts, observation, player, rules, simulation, strategy};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Number of distinct cards: 13 of each colour, the Wild and the Wild Draw.
+pub const NUM_CARD_KINDS: usize = 54;
+
+/// Number of coloured cards, which come first in the card kinds.
+const NUM_COLOURED_KINDS: usize = 52;
+
+/// First action playing a Wild card, one per declared colour.
+const PLAY_WILD: usize = NUM_COLOURED_KINDS;
+/// First action playing a Wild Draw card, one per declared colour.
+const PLAY_WILD_DRAW: usize = PLAY_WILD + 4;
+pub const DRAW: usize = PLAY_WILD_DRAW + 4;
+/// Keeps the card just drawn.
+pub const PASS: usize = DRAW + 1;
+pub const UNO: usize = PASS + 1;
+pub const NUM_ACTIONS: usize = UNO + 1;
+
+/// Largest number of opponents the observation has room for.
+pub const MAX_OPPONENTS: usize = 9;
+
+const HAND: usize = 0;
+const TOP_CARD: usize = HAND + NUM_CARD_KINDS;
+const COLOUR_IN_PLAY: usize = TOP_CARD + NUM_CARD_KINDS;
+const DISCARD_PILE: usize = COLOUR_IN_PLAY + 4;
+const OPPONENT_HAND_SIZES: usize = DISCARD_PILE + NUM_CARD_KINDS;
+const DRAW_PILE_SIZE: usize = OPPONENT_HAND_SIZES + MAX_OPPONENTS;
+const PENDING_PENALTY: usize = DRAW_PILE_SIZE + 1;
+const IS_FLOW_CLOCKWISE: usize = PENDING_PENALTY + 1;
+pub const OBSERVATION_SIZE: usize = IS_FLOW_CLOCKWISE + 1;
+
+/// Seat of the agent being trained.
+const AGENT: usize = 0;
+
+/// Represents errors that can occur when stepping the environment.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The action is out of range or not legal in the current position.
+    IllegalAction,
+    /// The episode is over, the environment has to be reset.
+    GameOver,
+}
+
+/// Gym-style environment for training an agent against AI players.
+///
+/// The agent sits in seat 0 and every episode is one game. `step` plays the agent's
+/// move and the AI players' moves until it is the agent's turn to decide again, and the
+/// reward is 1 if the agent wins, -1 if an AI player wins and 0 otherwise.
+///
+/// Actions are indexes below `NUM_ACTIONS`: playing one of the 52 coloured cards, a
+/// Wild or a Wild Draw with each declared colour, `DRAW`, `PASS` and `UNO`. Identical
+/// cards share an action. Observations are `OBSERVATION_SIZE` numbers holding the
+/// agent's hand, the top card, the colour in play, the discard pile, the opponents' hand
+/// sizes, the draw pile size, the pending penalty and the direction of play.
+///
+/// Decisions the agent does not make, such as challenges, are made by a Normal AI.
+pub struct Env {
+    opponents: Vec<strategy::Difficulty>,
+    rules: rules::Rules,
+    game: game::Game,
+    /// Action the agent's actor returns the next time it is asked.
+    next_action: Rc<Cell<Option<actor::UserAction>>>,
+    observation: [f32; OBSERVATION_SIZE],
+    action_mask: [bool; NUM_ACTIONS],
+    /// Legal action of the agent behind each action index. Identical cards share an
+    /// index, so the first legal one is played, e.g. only the card just drawn may be
+    /// played after drawing even if the hand holds a copy.
+    legal_actions: [Option<actor::UserAction>; NUM_ACTIONS],
+    is_done: bool,
+}
+
+/// Actor that plays the actions chosen by the agent.
+struct AgentActor {
+    ai: ai::AiActor,
+    next_action: Rc<Cell<Option<actor::UserAction>>>,
+}
+
+impl actor::Actor for AgentActor {
+    fn get_id(&self) -> usize {
+        self.ai.get_id()
+    }
+
+    fn get_player(&self) -> &player::Player {
+        self.ai.get_player()
+    }
+
+    fn get_player_mut(&mut self) -> &mut player::Player {
+        self.ai.get_player_mut()
+    }
+
+    fn get_turn_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.next_action
+            .take()
+            .unwrap_or_else(|| self.ai.get_turn_action(legal_actions, observation))
+    }
+
+    fn get_drawn_card_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.next_action
+            .take()
+            .unwrap_or_else(|| self.ai.get_drawn_card_action(legal_actions, observation))
+    }
+
+    fn get_stack_action(
+        &mut self,
+        penalty: usize,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.next_action.take().unwrap_or_else(|| {
+            self.ai
+                .get_stack_action(penalty, legal_actions, observation)
+        })
+    }
+
+    fn decide_challenge(&mut self) -> bool {
+        self.ai.decide_challenge()
+    }
+
+    fn catch_missed_uno(&mut self, offender_id: usize) -> bool {
+        self.ai.catch_missed_uno(offender_id)
+    }
+
+    fn decide_jump_in(&mut self, card_index: usize) -> bool {
+        self.ai.decide_jump_in(card_index)
+    }
+
+    fn get_swap_target(&mut self, hand_sizes: &[usize]) -> usize {
+        self.ai.get_swap_target(hand_sizes)
+    }
+
+    fn get_color_choice(&mut self, observation: &observation::Observation) -> card::Colour {
+        self.ai.get_color_choice(observation)
+    }
+
+    fn pre_turn_action(&mut self) {
+        self.ai.pre_turn_action();
+    }
+
+    fn post_turn_action(&mut self) {
+        self.ai.post_turn_action();
+    }
+}
+
+impl Env {
+    /// Creates an environment where the agent plays against an AI player of each
+    /// difficulty in `opponents`, and starts the first episode.
+    pub fn new(
+        opponents: Vec<strategy::Difficulty>,
+        rules: rules::Rules,
+        seed: u64,
+    ) -> Result<Env, String> {
+        if opponents.is_empty() || opponents.len() > MAX_OPPONENTS {
+            return Err(format!(
+                "The number of opponents must be between 1 and {MAX_OPPONENTS}"
+            ));
+        }
+        rules.check_game_attributes(opponents.len() + 1)?;
+        let next_action = Rc::new(Cell::new(None));
+        let game = Env::new_game(&opponents, &rules, &next_action, seed);
+        let mut env = Env {
+            opponents,
+            rules,
+            game,
+            next_action,
+            observation: [0.0; OBSERVATION_SIZE],
+            action_mask: [false; NUM_ACTIONS],
+            legal_actions: [None; NUM_ACTIONS],
+            is_done: false,
+        };
+        env.advance();
+        Ok(env)
+    }
+
+    fn new_game(
+        opponents: &[strategy::Difficulty],
+        rules: &rules::Rules,
+        next_action: &Rc<Cell<Option<actor::UserAction>>>,
+        seed: u64,
+    ) -> game::Game {
+        let budget = mcts::SearchBudget::Iterations(mcts::DEFAULT_ITERATIONS);
+        let agent = AgentActor {
+            ai: ai::AiActor::new(AGENT, Box::new(strategy::NormalStrategy), Some(seed)),
+            next_action: Rc::clone(next_action),
+        };
+        let mut actors: Vec<game::GameActor> = vec![Box::new(agent)];
+        actors.extend(opponents.iter().enumerate().map(|(i, difficulty)| {
+            game::create_ai_actor(i + 1, *difficulty, rules, budget, Some(seed))
+        }));
+        let mut game = game::Game::new(actors, rules.clone(), Some(seed));
+        // The dealer rotates so that the agent does not always play first
+        game.set_dealer((seed % (opponents.len() as u64 + 1)) as usize);
+        game
+    }
+
+    /// Starts a new episode and returns its first observation.
+    pub fn reset(&mut self, seed: u64) -> &[f32; OBSERVATION_SIZE] {
+        self.game = Env::new_game(&self.opponents, &self.rules, &self.next_action, seed);
+        self.is_done = false;
+        self.advance();
+        &self.observation
+    }
+
+    /// Plays the action of the agent and the moves of the AI players until the agent has
+    /// to decide again or the game ends. Returns the observation, the reward and whether
+    /// the episode is over.
+    pub fn step(&mut self, action: usize) -> Result<(&[f32; OBSERVATION_SIZE], f32, bool), Error> {
+        if self.is_done {
+            return Err(Error::GameOver);
+        }
+        let action = self
+            .legal_actions
+            .get(action)
+            .copied()
+            .flatten()
+            .ok_or(Error::IllegalAction)?;
+        self.next_action.set(Some(action));
+        self.game.run_game_phase(self.game.get_state());
+        self.next_action.set(None);
+        self.advance();
+
+        let reward = match (self.is_done, self.game.get_winner()) {
+            (true, Some(AGENT)) => 1.0,
+            (true, Some(_)) => -1.0,
+            _ => 0.0,
+        };
+        Ok((&self.observation, reward, self.is_done))
+    }
+
+    pub fn observation(&self) -> &[f32; OBSERVATION_SIZE] {
+        &self.observation
+    }
+
+    /// Returns which actions are legal for the agent, indexed like the actions.
+    pub fn action_mask(&self) -> &[bool; NUM_ACTIONS] {
+        &self.action_mask
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.is_done
+    }
+
+    fn is_agent_decision(&self) -> bool {
+        let is_decision = matches!(
+            self.game.get_state(),
+            GameState::GetPlayerAction | GameState::PlayDrawnCard | GameState::DecideStack
+        );
+        is_decision && self.game.get_current_actor().get_id() == AGENT
+    }
+
+    /// Runs the game until the agent has a decision to make or the game ends, then
+    /// updates the observation and the action mask.
+    fn advance(&mut self) {
+        for _ in 0..simulation::MAX_GAME_PHASES {
+            let state = self.game.get_state();
+            if matches!(state, GameState::End) || self.is_agent_decision() {
+                break;
+            }
+            self.game.run_game_phase(state);
+        }
+
+        self.action_mask = [false; NUM_ACTIONS];
+        self.legal_actions = [None; NUM_ACTIONS];
+        // A game that nobody can win is cut short
+        self.is_done = !self.is_agent_decision();
+        if !self.is_done {
+            let hand = self.game.get_actor(AGENT).get_player().get_hand();
+            for action in self.game.iter_legal_actions(AGENT) {
+                if let Some(index) = encode_action(action, hand) {
+                    self.action_mask[index] = true;
+                    self.legal_actions[index].get_or_insert(action);
+                }
+            }
+        }
+        encode_observation(&self.game, &mut self.observation);
+    }
+}
+
+/// Returns the index of a card among the card kinds.
+pub fn card_kind(card: &card::Card) -> usize {
+    let value = match card.value {
+        card::Value::Number(n) => n.min(9),
+        card::Value::Skip => 10,
+        card::Value::Reverse => 11,
+        card::Value::DrawTwo => 12,
+        card::Value::Wild => return NUM_COLOURED_KINDS,
+        card::Value::WildDraw(_) => return NUM_COLOURED_KINDS + 1,
+    };
+    card::colour_index(card.colour).map_or(NUM_COLOURED_KINDS, |colour| colour * 13 + value)
+}
+
+/// Returns the index of `action` for the agent holding `hand`.
+pub fn encode_action(action: actor::UserAction, hand: &[card::Card]) -> Option<usize> {
+    match action {
+        actor::UserAction::Draw => Some(DRAW),
+        actor::UserAction::Pass => Some(PASS),
+        actor::UserAction::Uno => Some(UNO),
+        actor::UserAction::Play(i) => Some(card_kind(hand.get(i)?)),
+        actor::UserAction::PlayWild(i, colour) => {
+            let first = match hand.get(i)?.value {
+                card::Value::WildDraw(_) => PLAY_WILD_DRAW,
+                _ => PLAY_WILD,
+            };
+            Some(first + card::colour_index(colour)?)
+        }
+    }
+}
+
+/// Writes what the agent sees of `game` into `observation` without allocating.
+fn encode_observation(game: &game::Game, observation: &mut [f32; OBSERVATION_SIZE]) {
+    observation.fill(0.0);
+    for card in game.get_actor(AGENT).get_player().get_hand() {
+        observation[HAND + card_kind(card)] += 1.0;
+    }
+    observation[TOP_CARD + card_kind(&game.get_top_card())] = 1.0;
+    if let Some(colour) = game.get_colour_in_play().and_then(card::colour_index) {
+        observation[COLOUR_IN_PLAY + colour] = 1.0;
+    }
+    for card in game.discarded_cards() {
+        observation[DISCARD_PILE + card_kind(card)] += 1.0;
+    }
+    let mut opponent = game.get_next_player(AGENT);
+    for slot in 0..MAX_OPPONENTS {
+        if opponent == AGENT {
+            break;
+        }
+        let hand_size = game.get_actor(opponent).get_player().get_number_of_cards();
+        observation[OPPONENT_HAND_SIZES + slot] = hand_size as f32;
+        opponent = game.get_next_player(opponent);
+    }
+    observation[DRAW_PILE_SIZE] = game.get_draw_pile_size() as f32;
+    observation[PENDING_PENALTY] = game.get_pending_penalty() as f32;
+    observation[IS_FLOW_CLOCKWISE] = if game.is_flow_clockwise() { 1.0 } else { 0.0 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Actor;
+    use crate::actor::UserAction;
+    use crate::card::{Card, Colour, Value};
+    use crate::deck;
+    use crate::default_deck::GAME_DECK;
+    use crate::strategy::Difficulty;
+
+    fn env() -> Env {
+        Env::new(vec![Difficulty::Normal], rules::Rules::default(), 0).unwrap()
+    }
+
+    #[test]
+    fn test_every_card_has_a_kind() {
+        let mut kinds: Vec<usize> = GAME_DECK.iter().map(card_kind).collect();
+        kinds.sort();
+        kinds.dedup();
+        assert_eq!(kinds, (0..NUM_CARD_KINDS).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_actions_have_distinct_indexes() {
+        let hand = [
+            Card {
+                colour: Colour::Green,
+                value: Value::Skip,
+            },
+            Card {
+                colour: Colour::Wild,
+                value: Value::WildDraw(4),
+            },
+        ];
+        let legal_actions = [
+            UserAction::Play(0),
+            UserAction::PlayWild(1, Colour::Blue),
+            UserAction::Draw,
+            UserAction::Pass,
+            UserAction::Uno,
+        ];
+        let mut indexes: Vec<usize> = legal_actions
+            .iter()
+            .map(|action| encode_action(*action, &hand).unwrap())
+            .collect();
+        assert!(indexes.iter().all(|index| *index < NUM_ACTIONS));
+        indexes.sort();
+        indexes.dedup();
+        assert_eq!(indexes.len(), legal_actions.len());
+    }
+
+    #[test]
+    fn test_step_plays_the_drawn_card_over_a_copy() {
+        let red = |value| Card {
+            colour: Colour::Red,
+            value,
+        };
+        let next_action = Rc::new(Cell::new(None));
+        let mut agent = AgentActor {
+            ai: ai::AiActor::new(AGENT, Box::new(strategy::NormalStrategy), Some(0)),
+            next_action: Rc::clone(&next_action),
+        };
+        agent.get_player_mut().take_card(red(Value::Number(5)));
+        let mut opponent = ai::AiActor::new(1, Box::new(strategy::NormalStrategy), Some(0));
+        opponent.get_player_mut().take_card(red(Value::Number(1)));
+        let deck = deck::Deck::from_piles(
+            vec![red(Value::Number(5)); 10],
+            vec![red(Value::Number(3))],
+            None,
+            Some(0),
+        );
+        let actors: Vec<game::GameActor> = vec![Box::new(agent), Box::new(opponent)];
+        let rules = rules::Rules::default();
+        let game = game::Game::from_position(actors, deck, rules.clone(), AGENT, true).unwrap();
+        let mut env = Env {
+            opponents: vec![Difficulty::Normal],
+            rules,
+            game,
+            next_action,
+            observation: [0.0; OBSERVATION_SIZE],
+            action_mask: [false; NUM_ACTIONS],
+            legal_actions: [None; NUM_ACTIONS],
+            is_done: false,
+        };
+        env.advance();
+
+        env.step(DRAW).unwrap();
+        assert_eq!(env.game.get_state(), GameState::PlayDrawnCard);
+        env.step(card_kind(&red(Value::Number(5)))).unwrap();
+        let played = observation::PlayRecord::CardPlayed {
+            player: AGENT,
+            card: red(Value::Number(5)),
+        };
+        assert!(env.game.observe(AGENT).history.contains(&played));
+    }
+
+    #[test]
+    fn test_reset_observes_the_starting_hand() {
+        let mut env = env();
+        let observation = env.reset(1);
+        let hand: f32 = observation[HAND..TOP_CARD].iter().sum();
+        assert!(hand >= 7.0);
+        assert_eq!(observation[OPPONENT_HAND_SIZES + 1], 0.0);
+        assert!(env.action_mask()[DRAW] || env.action_mask()[PASS]);
+    }
+
+    #[test]
+    fn test_illegal_action_is_rejected() {
+        let mut env = env();
+        let illegal = (0..NUM_ACTIONS).find(|action| !env.action_mask()[*action]);
+        assert_eq!(env.step(illegal.unwrap()), Err(Error::IllegalAction));
+        assert_eq!(env.step(NUM_ACTIONS), Err(Error::IllegalAction));
+    }
+
+    #[test]
+    fn test_episode_ends_with_a_reward() {
+        let mut env = env();
+        let mut reward = 0.0;
+        while !env.is_done() {
+            let action = (0..NUM_ACTIONS)
+                .find(|action| env.action_mask()[*action])
+                .unwrap();
+            reward = env.step(action).unwrap().1;
+        }
+        assert!(reward == 1.0 || reward == -1.0);
+        assert_eq!(env.step(DRAW), Err(Error::GameOver));
+    }
+
+    #[test]
+    fn test_same_seed_same_episode() {
+        let mut env = env();
+        let first = *env.reset(7);
+        let second = *env.reset(7);
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::card;
+use serde::Serialize;
+
+/// Something that happened in a game, reported to the game's observers.
+///
+/// Unlike `observation::PlayRecord`, events are not limited to what the players can
+/// see: `CardDrawn` tells which card was drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Event {
+    /// The player with ID `player` played `card`.
+    CardPlayed { player: usize, card: card::Card },
+    /// The player with ID `player` drew `card`, including the cards dealt to them.
+    CardDrawn { player: usize, card: card::Card },
+    /// The player with ID `player` chose the colour in play after a wild card.
+    ColourChosen { player: usize, colour: card::Colour },
+    /// The direction of play changed.
+    DirectionReversed { is_flow_clockwise: bool },
+    /// The player with ID `player` lost their turn.
+    PlayerSkipped { player: usize },
+    /// The player with ID `player` has to draw `num_of_cards` as a penalty. The
+    /// cards drawn follow as `CardDrawn` events.
+    PenaltyApplied { player: usize, num_of_cards: usize },
+    /// The discard pile was shuffled into the draw pile, which now holds `draw_pile_size` cards.
+    DeckReshuffled { draw_pile_size: usize },
+    /// The player with ID `player` emptied their hand.
+    RoundWon { player: usize },
+}
+
+/// Receives the events of a game, in the order they happen.
+///
+/// Closures taking an `&Event` are observers, e.g. to collect the events:
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use uno::event::Event;
+/// use uno::flow::GameFlow;
+/// use uno::{ai, game, rules, strategy};
+///
+/// let actors = (0..2)
+///     .map(|id| {
+///         let actor = ai::AiActor::new(id, Box::new(strategy::NormalStrategy), Some(0));
+///         Box::new(actor) as game::GameActor
+///     })
+///     .collect();
+/// let mut game = game::Game::new(actors, rules::Rules::default(), Some(0));
+///
+/// let events = Rc::new(RefCell::new(Vec::new()));
+/// let recorder = Rc::clone(&events);
+/// game.add_observer(Box::new(move |event: &Event| recorder.borrow_mut().push(*event)));
+/// game.play_to_end(100_000);
+///
+/// let winner = game.get_winner().unwrap();
+/// assert_eq!(events.borrow().last(), Some(&Event::RoundWon { player: winner }));
+/// ```
+pub trait Observer {
+    fn on_event(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Observer for F {
+    fn on_event(&mut self, event: &Event) {
+        self(event)
+    }
+}
@@ -1,13 +1,21 @@
 use crate::game;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GameState {
     Init,
     TurnStarts,
     GetPlayerAction,
     ExecutePlayerAction(game::GameAction),
+    PlayDrawnCard,
     ChooseColour,
+    SwapHands,
+    DecideStack,
+    DrawPenalty,
+    ChallengeWildDraw,
     EndTurn,
+    CatchMissedUno,
+    JumpIn,
     EndGame,
     End,
 }
@@ -19,18 +27,42 @@ pub trait GameFlow {
             let state = self.get_state();
             self.run_game_phase(state);
             let new_state = self.get_state();
-            continue_game = !matches!(new_state, GameState::End);
+            continue_game = !matches!(new_state, GameState::End) && !self.is_suspended();
         }
     }
 
+    /// Runs the game like `start_game`, giving up after `max_phases` game phases in case
+    /// nobody can ever win, e.g. when every card is in the players' hands.
+    /// Returns `true` if the game ended.
+    fn play_to_end(&mut self, max_phases: usize) -> bool {
+        for _ in 0..max_phases {
+            let state = self.get_state();
+            if matches!(state, GameState::End) {
+                return true;
+            }
+            if self.is_suspended() {
+                return false;
+            }
+            self.run_game_phase(state);
+        }
+        matches!(self.get_state(), GameState::End)
+    }
+
     fn run_game_phase(&mut self, state: GameState) {
         let new_state = match state {
             GameState::Init => self.handle_init(),
             GameState::TurnStarts => self.handle_turn_start(),
             GameState::GetPlayerAction => self.handle_get_player_action(),
             GameState::ExecutePlayerAction(action) => self.handle_execute_player_action(&action),
+            GameState::PlayDrawnCard => self.handle_play_drawn_card(),
             GameState::ChooseColour => self.handle_choose_colour(),
+            GameState::SwapHands => self.handle_swap_hands(),
+            GameState::DecideStack => self.handle_decide_stack(),
+            GameState::DrawPenalty => self.handle_draw_penalty(),
+            GameState::ChallengeWildDraw => self.handle_challenge_wild_draw(),
             GameState::EndTurn => self.handle_end_turn(),
+            GameState::CatchMissedUno => self.handle_catch_missed_uno(),
+            GameState::JumpIn => self.handle_jump_in(),
             GameState::EndGame => self.handle_end_game(),
             GameState::End => GameState::End,
         };
@@ -39,11 +71,20 @@ pub trait GameFlow {
 
     fn set_state(&mut self, state: GameState);
     fn get_state(&self) -> GameState;
+    /// Tells whether an actor suspended the game, which stops `start_game`.
+    fn is_suspended(&self) -> bool;
     fn handle_init(&mut self) -> GameState;
     fn handle_turn_start(&mut self) -> GameState;
     fn handle_get_player_action(&mut self) -> GameState;
     fn handle_execute_player_action(&mut self, action: &game::GameAction) -> GameState;
+    fn handle_play_drawn_card(&mut self) -> GameState;
     fn handle_choose_colour(&mut self) -> GameState;
+    fn handle_swap_hands(&mut self) -> GameState;
+    fn handle_decide_stack(&mut self) -> GameState;
+    fn handle_draw_penalty(&mut self) -> GameState;
+    fn handle_challenge_wild_draw(&mut self) -> GameState;
     fn handle_end_turn(&mut self) -> GameState;
+    fn handle_catch_missed_uno(&mut self) -> GameState;
+    fn handle_jump_in(&mut self) -> GameState;
     fn handle_end_game(&mut self) -> GameState;
 }
@@ -1,14 +1,22 @@
 use crate::actor;
 use crate::ai;
+use crate::bot;
 use crate::card;
 use crate::deck;
 use crate::deck::DeckTrait;
+use crate::event;
 use crate::flow;
+use crate::mcts;
+use crate::observation;
 use crate::player;
-use crate::ui;
+use crate::rules;
+use crate::strategy;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use std::time::Duration;
 
 type GameResult<T> = Result<T, Error>;
-type GameActor = Box<dyn actor::Actor>;
+pub type GameActor = Box<dyn actor::Actor>;
 
 pub enum Error {
     DrawPileIsEmpty,
@@ -16,24 +24,24 @@ pub enum Error {
     Unknown,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GameAction {
     None,
     PlayerDraw,
     PlayerPlaysCard(usize),
+    PlayerPlaysWild(usize, card::Colour),
     ChooseColour,
+    DeclareUno,
+    SwapHands,
 }
 
-pub fn check_game_attributes(num_of_players: usize, num_of_cards: usize) -> Result<(), String> {
-    if num_of_cards > 10 {
-        return Err("The maximum number of cards is 10".to_string());
-    }
-
-    if num_of_players > 10 {
-        return Err("The maximum number of players is 10".to_string());
-    }
-
-    Ok(())
+/// A Wild Draw card that the affected player may still challenge.
+#[derive(Clone, Serialize, Deserialize)]
+struct WildDrawChallenge {
+    offender: usize,
+    victim: usize,
+    previous_colour: card::Colour,
+    num_of_cards: usize,
 }
 
 pub struct Game {
@@ -43,16 +51,69 @@ pub struct Game {
     deck: deck::Deck,
     actor_index: usize,
     is_flow_clockwise: bool,
-    num_of_cards: usize,
+    dealer: usize,
+    rules: rules::Rules,
+    pending_penalty: usize,
+    challenge: Option<WildDrawChallenge>,
+    uno_declared: bool,
+    /// Whether the hands were swapped or rotated this turn. A player left with one card
+    /// by an exchange did not play their second-to-last card, so has no UNO to declare.
+    hands_exchanged: bool,
+    has_played_card: bool,
+    skip_next_actor: bool,
+    chosen_colour: Option<card::Colour>,
+    /// Shared with the observations, so that observing the game does not copy it.
+    history: Rc<Vec<observation::PlayRecord>>,
+    num_of_turns: usize,
+    num_of_refills: usize,
+    observers: Vec<Box<dyn event::Observer>>,
+    is_suspended: bool,
+    /// Why the game stopped, if it could not be rewound.
+    error: Option<String>,
+    /// Indexes of the actors who can take their turns back.
+    undo_actors: Vec<usize>,
+    /// Snapshots taken at the start of the turns of the actors who can take their turns
+    /// back, with the index of the actor whose turn it is.
+    undo_history: Vec<(usize, Snapshot)>,
+}
+
+/// Everything needed to restore a game: the state of the engine, the players' hands
+/// and the internal state of the actors. Observers are not part of it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    state: flow::GameState,
+    players: Vec<player::Player>,
+    actor_states: Vec<serde_json::Value>,
+    deck: deck::Deck,
+    actor_index: usize,
+    is_flow_clockwise: bool,
+    dealer: usize,
+    rules: rules::Rules,
+    pending_penalty: usize,
+    challenge: Option<WildDrawChallenge>,
+    uno_declared: bool,
+    #[serde(default)]
+    hands_exchanged: bool,
+    has_played_card: bool,
+    skip_next_actor: bool,
+    chosen_colour: Option<card::Colour>,
+    history: Vec<observation::PlayRecord>,
+    num_of_turns: usize,
+    num_of_refills: usize,
+    /// Turns that can still be taken back, empty in the snapshots of the history itself.
+    #[serde(default)]
+    undo_history: Vec<(usize, Snapshot)>,
 }
 
 impl Game {
     fn player_draws(&mut self, actor_index: usize) -> GameResult<GameAction> {
         match self.deck.draw() {
             Ok(card) => {
-                self.get_actor_mut(actor_index)
-                    .get_player_mut()
-                    .take_card(card);
+                let actor = self.get_actor_mut(actor_index);
+                let player = actor.get_id();
+                actor.get_player_mut().take_card(card);
+                self.record_play(observation::PlayRecord::CardDrawn { player });
+                self.emit(event::Event::CardDrawn { player, card });
                 Ok(GameAction::PlayerDraw)
             }
             Err(deck::Error::DrawPileIsEmpty) => Err(Error::DrawPileIsEmpty),
@@ -64,7 +125,13 @@ impl Game {
         match self.player_draws(actor_index) {
             Ok(_) => Ok(GameAction::PlayerDraw),
             Err(Error::DrawPileIsEmpty) => {
-                let _ = self.deck.refill_draw_pile(); // No need to check for DiscardPileIsEmpty
+                // No need to check for DiscardPileIsEmpty
+                if self.deck.refill_draw_pile().is_ok() {
+                    self.num_of_refills += 1;
+                    self.emit(event::Event::DeckReshuffled {
+                        draw_pile_size: self.deck.number_of_cards_in_draw_pile(),
+                    });
+                }
                 self.player_draws(actor_index)
             }
             _ => Err(Error::Unknown),
@@ -79,15 +146,53 @@ impl Game {
         Ok(())
     }
 
+    /// Makes the actor at `actor_index` draw `num_of_cards` as a penalty.
+    fn apply_penalty(&mut self, actor_index: usize, num_of_cards: usize) {
+        let player = self.get_actor(actor_index).get_id();
+        self.record_play(observation::PlayRecord::PenaltyApplied {
+            player,
+            num_of_cards,
+        });
+        self.emit(event::Event::PenaltyApplied {
+            player,
+            num_of_cards,
+        });
+        if let Err(Error::DrawPileIsEmpty) = self.player_draws_multiple(actor_index, num_of_cards) {
+            // There are not enough cards on the draw and discard piles to take the whole penalty
+        }
+    }
+
     pub fn change_wild_color(&mut self, colour: &card::Colour) {
-        self.deck.change_colour_of_top_card_in_discard(colour);
+        self.deck.declare_colour(colour);
+        let player = self.get_current_actor().get_id();
+        self.record_play(observation::PlayRecord::ColourDeclared {
+            player,
+            colour: *colour,
+        });
+        self.emit(event::Event::ColourChosen {
+            player,
+            colour: *colour,
+        });
     }
 
-    fn handle_wild_draw(&mut self, affected_actor_index: usize, num_of_cards: usize) -> GameAction {
-        if let Err(Error::DrawPileIsEmpty) =
-            self.player_draws_multiple(affected_actor_index, num_of_cards)
-        {
-            // There are not enough cards on the draw and discard piles to take two cards
+    fn handle_wild_draw(
+        &mut self,
+        actor_index: usize,
+        affected_actor_index: usize,
+        num_of_cards: usize,
+    ) -> GameAction {
+        if self.rules.stacking {
+            self.pending_penalty += num_of_cards;
+        } else if let Ok(previous_colour) = self.deck.get_current_colour() {
+            // The penalty is drawn once the affected player decides whether to challenge
+            self.challenge = Some(WildDrawChallenge {
+                offender: actor_index,
+                victim: affected_actor_index,
+                previous_colour,
+                num_of_cards,
+            });
+        } else {
+            self.apply_penalty(affected_actor_index, num_of_cards);
         }
 
         self.handle_wild()
@@ -98,18 +203,37 @@ impl Game {
     }
 
     fn handle_reverse(&mut self) -> GameAction {
+        if self.rules.two_player_reverse_skips && self.actors.len() == 2 {
+            return self.handle_skip();
+        }
         self.revese_direction();
         GameAction::None
     }
 
     fn handle_skip(&mut self) -> GameAction {
-        self.set_next_actor();
+        self.skip_next_actor = true;
+        GameAction::None
+    }
+
+    fn handle_seven(&mut self, actor_index: usize) -> GameAction {
+        if self.has_player_won(actor_index) {
+            return GameAction::None;
+        }
+        GameAction::SwapHands
+    }
+
+    fn handle_zero(&mut self, actor_index: usize) -> GameAction {
+        if !self.has_player_won(actor_index) {
+            self.rotate_hands();
+        }
         GameAction::None
     }
 
     fn handle_draw_two(&mut self, affected_actor_index: usize) -> GameAction {
-        if let Err(Error::DrawPileIsEmpty) = self.player_draws_multiple(affected_actor_index, 2) {
-            // There are not enough cards on the draw and discard piles to take two cards
+        if self.rules.stacking {
+            self.pending_penalty += 2;
+        } else {
+            self.apply_penalty(affected_actor_index, 2);
         }
         GameAction::None
     }
@@ -120,12 +244,53 @@ impl Game {
             card::Value::Skip => self.handle_skip(),
             card::Value::Reverse => self.handle_reverse(),
             card::Value::Wild => self.handle_wild(),
-            card::Value::WildDraw(n) => self.handle_wild_draw(self.get_next_player(actor_index), n),
+            card::Value::WildDraw(n) => {
+                self.handle_wild_draw(actor_index, self.get_next_player(actor_index), n)
+            }
+            card::Value::Number(7) if self.rules.seven_o => self.handle_seven(actor_index),
+            card::Value::Number(0) if self.rules.seven_o => self.handle_zero(actor_index),
             card::Value::Number(_) => GameAction::None,
         }
     }
 
-    fn get_next_player(&self, current_actor_index: usize) -> usize {
+    fn swap_hands(&mut self, actor_index: usize, other_actor_index: usize) {
+        let hand = self
+            .get_actor_mut(actor_index)
+            .get_player_mut()
+            .replace_hand(Vec::new());
+        let other_hand = self
+            .get_actor_mut(other_actor_index)
+            .get_player_mut()
+            .replace_hand(hand);
+        self.get_actor_mut(actor_index)
+            .get_player_mut()
+            .replace_hand(other_hand);
+        self.hands_exchanged = true;
+        self.record_play(observation::PlayRecord::HandsSwapped {
+            player: self.get_actor(actor_index).get_id(),
+            other: self.get_actor(other_actor_index).get_id(),
+        });
+    }
+
+    /// Passes every hand to the next player in the direction of play.
+    fn rotate_hands(&mut self) {
+        let hands: Vec<Vec<card::Card>> = self
+            .actors
+            .iter_mut()
+            .map(|actor| actor.get_player_mut().replace_hand(Vec::new()))
+            .collect();
+        for (index, hand) in hands.into_iter().enumerate() {
+            let next_index = self.get_next_player(index);
+            self.get_actor_mut(next_index)
+                .get_player_mut()
+                .replace_hand(hand);
+        }
+        self.hands_exchanged = true;
+        self.record_play(observation::PlayRecord::HandsRotated);
+    }
+
+    /// Returns the index of the actor who plays after the actor at `current_actor_index`.
+    pub fn get_next_player(&self, current_actor_index: usize) -> usize {
         if !self.is_flow_clockwise && current_actor_index == 0 {
             self.actors.len() - 1
         } else {
@@ -134,34 +299,117 @@ impl Game {
         }
     }
 
+    /// Lists the indexes of the other actors in turn order, starting with the next actor.
+    fn opponents_in_turn_order(&self, actor_index: usize) -> Vec<usize> {
+        let mut opponents = Vec::new();
+        let mut index = self.get_next_player(actor_index);
+        while index != actor_index {
+            opponents.push(index);
+            index = self.get_next_player(index);
+        }
+        opponents
+    }
+
+    /// Builds what the actor at `actor_index` can see of the game.
+    pub fn observe(&self, actor_index: usize) -> observation::Observation {
+        let top_card = self.get_top_card();
+        let declared_colour = self
+            .deck
+            .get_current_colour()
+            .ok()
+            .filter(|colour| *colour != top_card.colour);
+        let opponents = self.opponents_in_turn_order(actor_index);
+        observation::Observation {
+            top_card,
+            declared_colour,
+            opponent_ids: opponents
+                .iter()
+                .map(|index| self.get_actor(*index).get_id())
+                .collect(),
+            opponent_hand_sizes: opponents
+                .iter()
+                .map(|index| self.get_actor(*index).get_player().get_number_of_cards())
+                .collect(),
+            discard_pile: self.deck.get_discard_pile(),
+            draw_pile_size: self.deck.number_of_cards_in_draw_pile(),
+            pending_penalty: self.pending_penalty,
+            is_flow_clockwise: self.is_flow_clockwise,
+            history: Rc::clone(&self.history),
+        }
+    }
+
+    /// Adds a record to the history every player can see. The history is only copied
+    /// if an observation handed out earlier is still alive.
+    fn record_play(&mut self, record: observation::PlayRecord) {
+        Rc::make_mut(&mut self.history).push(record);
+    }
+
     fn revese_direction(&mut self) {
         self.is_flow_clockwise = !self.is_flow_clockwise;
+        self.emit(event::Event::DirectionReversed {
+            is_flow_clockwise: self.is_flow_clockwise,
+        });
     }
 
     fn is_valid_play(&self, card: &card::Card) -> bool {
-        match self.deck.get_top_card() {
-            Ok(card_on_top) => {
-                card.colour == card_on_top.colour
+        match (self.deck.get_top_card(), self.deck.get_current_colour()) {
+            (Ok(card_on_top), Ok(colour_in_play)) => {
+                card.colour == colour_in_play
                     || card.value == card_on_top.value
                     || card.colour == card::Colour::Wild
-                    || card_on_top.colour == card::Colour::Wild
+                    || colour_in_play == card::Colour::Wild
             }
-            Err(deck::Error::DiscardPileIsEmpty) => {
+            (Err(deck::Error::DiscardPileIsEmpty), _) => {
                 // There is no card on top of the discard pile (for some reason)
                 // So might as well play whatever the player wants
                 true
             }
-            Err(_) => unimplemented!(),
+            // The colour in play comes from the top card, so the deck cannot report one
+            // without the other
+            (Ok(_), Err(_)) | (Err(deck::Error::DrawPileIsEmpty), _) => false,
         }
     }
 
+    /// Checks if the player could have played a card of the colour that was in play
+    /// before the Wild Draw, which makes playing the Wild Draw illegal.
+    fn could_have_followed_colour(
+        &self,
+        actor_index: usize,
+        previous_colour: card::Colour,
+    ) -> bool {
+        previous_colour != card::Colour::Wild
+            && self
+                .get_actor(actor_index)
+                .get_player()
+                .get_hand()
+                .iter()
+                .any(|card| card.colour == previous_colour)
+    }
+
+    fn resolve_challenge(&mut self, challenge: WildDrawChallenge, is_challenged: bool) {
+        let (penalised_actor_index, num_of_cards) = if !is_challenged {
+            (challenge.victim, challenge.num_of_cards)
+        } else if self.could_have_followed_colour(challenge.offender, challenge.previous_colour) {
+            (challenge.offender, challenge.num_of_cards)
+        } else {
+            (challenge.victim, challenge.num_of_cards + 2)
+        };
+        self.apply_penalty(penalised_actor_index, num_of_cards);
+    }
+
     pub fn get_player_action(
         &self,
         player: &player::Player,
         action: actor::UserAction,
     ) -> GameResult<GameAction> {
         match action {
-            actor::UserAction::Draw => Ok(GameAction::PlayerDraw),
+            actor::UserAction::Draw => {
+                if self.rules.forced_play && self.has_valid_play(player) {
+                    Err(Error::InvalidPlay)
+                } else {
+                    Ok(GameAction::PlayerDraw)
+                }
+            }
             actor::UserAction::Play(i) => {
                 if let Ok(card) = player.get_card(i) {
                     if self.is_valid_play(card) {
@@ -173,6 +421,117 @@ impl Game {
                     Err(Error::InvalidPlay)
                 }
             }
+            actor::UserAction::PlayWild(i, colour) => match player.get_card(i) {
+                Ok(card)
+                    if card.colour == card::Colour::Wild
+                        && colour != card::Colour::Wild
+                        && self.is_valid_play(card) =>
+                {
+                    Ok(GameAction::PlayerPlaysWild(i, colour))
+                }
+                _ => Err(Error::InvalidPlay),
+            },
+            actor::UserAction::Uno if self.can_declare_uno(player) => Ok(GameAction::DeclareUno),
+            actor::UserAction::Uno | actor::UserAction::Pass => Err(Error::InvalidPlay),
+        }
+    }
+
+    /// UNO is declared before playing the second to last card, and only once.
+    fn can_declare_uno(&self, player: &player::Player) -> bool {
+        !self.uno_declared && player.get_number_of_cards() == 2
+    }
+
+    /// Lists every action the actor at `actor_index` may take in the current state.
+    /// Wild cards are listed once per colour they can be played as.
+    pub fn legal_actions(&self, actor_index: usize) -> Vec<actor::UserAction> {
+        self.iter_legal_actions(actor_index).collect()
+    }
+
+    /// Iterates over the actions `legal_actions` lists, without allocating.
+    pub fn iter_legal_actions(
+        &self,
+        actor_index: usize,
+    ) -> impl Iterator<Item = actor::UserAction> + '_ {
+        let player = self.get_actor(actor_index).get_player();
+        let plays = player.get_hand().iter().enumerate().flat_map(|(i, card)| {
+            let is_wild = card.colour == card::Colour::Wild;
+            let colours: &[card::Colour] = if is_wild { &card::COLOURS } else { &[] };
+            let play = (!is_wild).then_some(actor::UserAction::Play(i));
+            play.into_iter().chain(
+                colours
+                    .iter()
+                    .map(move |colour| actor::UserAction::PlayWild(i, *colour)),
+            )
+        });
+        let others = [
+            actor::UserAction::Uno,
+            actor::UserAction::Draw,
+            actor::UserAction::Pass,
+        ];
+
+        plays.chain(others).filter(move |action| {
+            match self.state {
+                flow::GameState::DecideStack => self.get_player_stack_action(player, *action),
+                flow::GameState::PlayDrawnCard => {
+                    self.get_player_drawn_card_action(player, *action)
+                }
+                _ => self.get_player_action(player, *action),
+            }
+            .is_ok()
+        })
+    }
+
+    /// Validates what the player does with the playable card they just drew.
+    /// Only the drawn card may be played, and passing keeps it.
+    pub fn get_player_drawn_card_action(
+        &self,
+        player: &player::Player,
+        action: actor::UserAction,
+    ) -> GameResult<GameAction> {
+        let drawn_card_index = player.get_number_of_cards().wrapping_sub(1);
+        match action {
+            actor::UserAction::Play(i) | actor::UserAction::PlayWild(i, _)
+                if i == drawn_card_index =>
+            {
+                self.get_player_action(player, action)
+            }
+            actor::UserAction::Uno if self.can_declare_uno(player) => Ok(GameAction::DeclareUno),
+            actor::UserAction::Pass => Ok(GameAction::None),
+            _ => Err(Error::InvalidPlay),
+        }
+    }
+
+    fn has_valid_play(&self, player: &player::Player) -> bool {
+        player
+            .get_hand()
+            .iter()
+            .any(|card| self.is_valid_play(card))
+    }
+
+    fn has_drawn_valid_play(&self, actor_index: usize) -> bool {
+        let hand = self.get_actor(actor_index).get_player().get_hand();
+        hand.last().is_some_and(|card| self.is_valid_play(card))
+    }
+
+    pub fn get_player_stack_action(
+        &self,
+        player: &player::Player,
+        action: actor::UserAction,
+    ) -> GameResult<GameAction> {
+        match action {
+            actor::UserAction::Draw => Ok(GameAction::PlayerDraw),
+            actor::UserAction::Play(i) => match player.get_card(i) {
+                Ok(card) if card.is_draw_card() => Ok(GameAction::PlayerPlaysCard(i)),
+                _ => Err(Error::InvalidPlay),
+            },
+            actor::UserAction::PlayWild(i, colour) => match player.get_card(i) {
+                Ok(card) if card.is_draw_card() && colour != card::Colour::Wild => {
+                    Ok(GameAction::PlayerPlaysWild(i, colour))
+                }
+                _ => Err(Error::InvalidPlay),
+            },
+            actor::UserAction::Uno if self.can_declare_uno(player) => Ok(GameAction::DeclareUno),
+            actor::UserAction::Uno | actor::UserAction::Pass => Err(Error::InvalidPlay),
         }
     }
 
@@ -182,13 +541,27 @@ impl Game {
         action: &GameAction,
     ) -> GameResult<GameAction> {
         match action {
-            GameAction::PlayerDraw => self.player_draws_with_pile_check(actor_index),
+            GameAction::PlayerDraw => {
+                self.player_draws_with_pile_check(actor_index)?;
+                while self.rules.draw_until_playable && !self.has_drawn_valid_play(actor_index) {
+                    self.player_draws_with_pile_check(actor_index)?;
+                }
+                Ok(GameAction::PlayerDraw)
+            }
+            GameAction::PlayerPlaysWild(index, colour) => {
+                self.chosen_colour = Some(*colour);
+                self.execute_player_action(actor_index, &GameAction::PlayerPlaysCard(*index))
+            }
             GameAction::PlayerPlaysCard(index) => {
                 if let Ok(mut card) = self
                     .get_actor_mut(actor_index)
                     .get_player_mut()
                     .play_card(*index)
                 {
+                    self.has_played_card = true;
+                    let player = self.get_actor(actor_index).get_id();
+                    self.record_play(observation::PlayRecord::CardPlayed { player, card });
+                    self.emit(event::Event::CardPlayed { player, card });
                     let result = self.execute_card_action(actor_index, &mut card);
                     self.deck.discard(card);
                     Ok(result)
@@ -200,11 +573,138 @@ impl Game {
         }
     }
 
+    /// Checks if the player is down to one card without having declared UNO. A hand
+    /// received in an exchange this turn does not count.
+    fn has_missed_uno(&self, actor_index: usize) -> bool {
+        !self.uno_declared
+            && !self.hands_exchanged
+            && self
+                .get_actor(actor_index)
+                .get_player()
+                .get_number_of_cards()
+                == 1
+    }
+
+    /// Gives the other players, in turn order, the chance to catch a player who did not
+    /// declare UNO. The first one to catch them makes them draw two cards.
+    /// Fails with the state to continue from if an actor gives a command instead of
+    /// answering. The actors are asked again from the first one when the game goes on.
+    fn catch_missed_uno(&mut self, offender_index: usize) -> Result<(), flow::GameState> {
+        let offender_id = self.get_actor(offender_index).get_id();
+        let mut index = self.get_next_player(offender_index);
+        while index != offender_index {
+            let is_caught = self.get_actor_mut(index).catch_missed_uno(offender_id);
+            if let Some(state) = self.run_command(index) {
+                return Err(state);
+            }
+            if is_caught {
+                self.apply_penalty(offender_index, 2);
+                return Ok(());
+            }
+            index = self.get_next_player(index);
+        }
+        Ok(())
+    }
+
+    /// Looks for a player, in turn order, who jumps in with a card identical to the one
+    /// on top of the discard pile. Wild cards cannot be jumped in on.
+    /// Returns the index of the player and of the card in their hand. Fails with the state
+    /// to continue from if an actor gives a command instead of answering.
+    fn find_jump_in(&mut self) -> Result<Option<(usize, usize)>, flow::GameState> {
+        let Ok(card_on_top) = self.deck.get_top_card().copied() else {
+            return Ok(None);
+        };
+        if card_on_top.colour == card::Colour::Wild {
+            return Ok(None);
+        }
+
+        let mut index = self.get_next_player(self.actor_index);
+        while index != self.actor_index {
+            let hand = self.get_actor(index).get_player().get_hand();
+            if let Some(card_index) = hand.iter().position(|card| *card == card_on_top) {
+                let jumps_in = self.get_actor_mut(index).decide_jump_in(card_index);
+                if let Some(state) = self.run_command(index) {
+                    return Err(state);
+                }
+                if jumps_in {
+                    return Ok(Some((index, card_index)));
+                }
+            }
+            index = self.get_next_player(index);
+        }
+        Ok(None)
+    }
+
+    fn close_turn(&mut self) {
+        self.uno_declared = false;
+        self.hands_exchanged = false;
+        self.has_played_card = false;
+        self.get_current_actor_mut().post_turn_action();
+    }
+
+    fn finish_turn(&mut self) -> flow::GameState {
+        if self.rules.jump_in && self.has_played_card {
+            flow::GameState::JumpIn
+        } else {
+            self.pass_turn()
+        }
+    }
+
+    fn pass_turn(&mut self) -> flow::GameState {
+        self.num_of_turns += 1;
+        self.close_turn();
+        self.set_next_actor();
+        if std::mem::take(&mut self.skip_next_actor) {
+            self.skip_current_actor();
+        }
+        flow::GameState::TurnStarts
+    }
+
+    /// Applies the effect of the card turned over to start the discard pile, following
+    /// the official rules. A Wild Draw is returned to the deck and replaced.
+    fn apply_starting_card(&mut self) {
+        while let Ok(card::Card {
+            value: card::Value::WildDraw(_),
+            ..
+        }) = self.deck.get_top_card()
+        {
+            if self.deck.replace_top_card().is_err() {
+                return;
+            }
+        }
+
+        let Ok(card) = self.deck.get_top_card() else {
+            return;
+        };
+        match card.value {
+            card::Value::Wild => {
+                let observation = self.observe(self.actor_index);
+                let colour = self.get_current_actor_mut().get_color_choice(&observation);
+                // A command suspends the game once it is dealt, without a colour in play
+                if self.run_command(self.actor_index).is_none() {
+                    self.change_wild_color(&colour);
+                }
+            }
+            card::Value::Skip => self.skip_current_actor(),
+            card::Value::Reverse => {
+                // The dealer plays first and play continues the other way
+                self.revese_direction();
+                self.actor_index = self.dealer;
+            }
+            card::Value::DrawTwo => {
+                self.apply_penalty(self.actor_index, 2);
+                self.skip_current_actor();
+            }
+            card::Value::Number(_) | card::Value::WildDraw(_) => {}
+        }
+    }
+
     pub fn deal_cards_to_players(&mut self) {
         let num_of_players = self.actors.len();
         for i in 0..num_of_players {
             assert!(
-                self.player_draws_multiple(i, self.num_of_cards).is_ok(),
+                self.player_draws_multiple(i, self.rules.starting_hand_size)
+                    .is_ok(),
                 "Failed to deal cards at the start of the game"
             );
         }
@@ -214,6 +714,25 @@ impl Game {
         self.actor_index = self.get_next_player(self.actor_index);
     }
 
+    /// Makes the current actor lose their turn to the next actor.
+    fn skip_current_actor(&mut self) {
+        self.emit(event::Event::PlayerSkipped {
+            player: self.get_current_actor().get_id(),
+        });
+        self.set_next_actor();
+    }
+
+    /// Registers an observer that receives every event of the game from now on.
+    pub fn add_observer(&mut self, observer: Box<dyn event::Observer>) {
+        self.observers.push(observer);
+    }
+
+    fn emit(&mut self, event: event::Event) {
+        for observer in self.observers.iter_mut() {
+            observer.on_event(&event);
+        }
+    }
+
     pub fn has_player_won(&self, actor_index: usize) -> bool {
         self.get_actor(actor_index).get_player().is_hand_empty()
     }
@@ -230,27 +749,385 @@ impl Game {
         &self.actors[index]
     }
 
+    pub fn get_number_of_actors(&self) -> usize {
+        self.actors.len()
+    }
+
     pub fn get_current_actor(&self) -> &GameActor {
         self.get_actor(self.actor_index)
     }
 
-    pub fn new(num_of_players: usize, num_of_cards: usize) -> Self {
-        let mut actors: Vec<Box<dyn actor::Actor>> = vec![Box::new(ui::HumanActor::new(0))];
-        actors.extend(
-            (1..num_of_players).map(|i| Box::new(ai::AiActor::new(i)) as Box<dyn actor::Actor>),
-        );
+    /// Returns the ID of the player who emptied their hand, if any.
+    pub fn get_winner(&self) -> Option<usize> {
+        self.actors
+            .iter()
+            .find(|actor| actor.get_player().is_hand_empty())
+            .map(|actor| actor.get_id())
+    }
+
+    /// Sets the dealer of the round. The player after the dealer plays first.
+    pub fn set_dealer(&mut self, dealer: usize) {
+        self.dealer = dealer;
+        self.actor_index = self.get_next_player(dealer);
+    }
+
+    /// Consumes the game and hands back its actors, e.g. to seat them in the next round.
+    pub fn into_actors(self) -> Vec<GameActor> {
+        self.actors
+    }
+
+    pub fn get_top_card(&self) -> card::Card {
+        // Dealing turns a card over, and positions and snapshots without one are rejected
+        *self.deck.get_top_card().unwrap()
+    }
+
+    /// Returns the colour cards have to match, `None` before the starting card is turned.
+    pub fn get_colour_in_play(&self) -> Option<card::Colour> {
+        self.deck.get_current_colour().ok()
+    }
+
+    /// Iterates over the discard pile from the bottom to the top without copying it.
+    pub fn discarded_cards(&self) -> impl Iterator<Item = &card::Card> {
+        self.deck.discarded_cards()
+    }
+
+    pub fn get_draw_pile_size(&self) -> usize {
+        self.deck.number_of_cards_in_draw_pile()
+    }
+
+    /// Returns the number of cards the current actor has to draw unless they stack.
+    pub fn get_pending_penalty(&self) -> usize {
+        self.pending_penalty
+    }
+
+    pub fn is_flow_clockwise(&self) -> bool {
+        self.is_flow_clockwise
+    }
+
+    /// Returns the number of turns played so far.
+    pub fn get_number_of_turns(&self) -> usize {
+        self.num_of_turns
+    }
 
+    /// Returns the number of times the draw pile was refilled from the discard pile.
+    pub fn get_number_of_refills(&self) -> usize {
+        self.num_of_refills
+    }
+
+    /// Records that the current actor declared UNO this turn.
+    pub fn declare_uno(&mut self) {
+        self.uno_declared = true;
+    }
+
+    /// Carries out the command the actor at `actor_index` gave instead of answering, if
+    /// any, and returns the state to continue from. Only the current actor can take their
+    /// turn back, if undo is enabled for them, the others are asked again.
+    fn run_command(&mut self, actor_index: usize) -> Option<flow::GameState> {
+        let can_undo = actor_index == self.actor_index && self.undo_actors.contains(&actor_index);
+        let state = match self.get_actor_mut(actor_index).take_command()? {
+            actor::Command::Suspend => self.suspend(),
+            actor::Command::Undo if can_undo => match self.undo() {
+                Ok(state) => state,
+                Err(error) => {
+                    // The position is lost, so the game cannot go on
+                    self.error = Some(error);
+                    self.is_suspended = true;
+                    self.state
+                }
+            },
+            actor::Command::Undo => self.state,
+        };
+        Some(state)
+    }
+
+    /// Stops the game when an actor asks to suspend it. The current state is kept, so the
+    /// actor is asked for their move again when the game is resumed.
+    fn suspend(&mut self) -> flow::GameState {
+        self.is_suspended = true;
+        self.state
+    }
+
+    /// Keeps a snapshot of every turn of the actor at `actor_index`, so that they can take
+    /// their turns back with `actor::Command::Undo`.
+    pub fn enable_undo(&mut self, actor_index: usize) {
+        if !self.undo_actors.contains(&actor_index) {
+            self.undo_actors.push(actor_index);
+        }
+    }
+
+    /// Returns why the game stopped before it ended, if it was not suspended by an actor,
+    /// e.g. because a turn could not be taken back.
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Rewinds the game to the start of the current actor's previous turn. The actor is
+    /// asked again if they have not had a previous turn yet. Fails if the snapshot of
+    /// that turn cannot be restored.
+    fn undo(&mut self) -> Result<flow::GameState, String> {
+        let previous_turn = self
+            .undo_history
+            .iter()
+            .rposition(|(actor_index, _)| *actor_index == self.actor_index)
+            .and_then(|current_turn| {
+                self.undo_history[..current_turn]
+                    .iter()
+                    .rposition(|(actor_index, _)| *actor_index == self.actor_index)
+            });
+        let Some(previous_turn) = previous_turn else {
+            return Ok(self.state);
+        };
+        // The turn is captured again when it starts over
+        let (_, mut snapshot) = self.undo_history[previous_turn].clone();
+        self.undo_history.truncate(previous_turn);
+        snapshot.undo_history = std::mem::take(&mut self.undo_history);
+        self.restore(snapshot)?;
+        Ok(self.state)
+    }
+
+    /// Lets `start_game` continue a suspended game.
+    pub fn resume(&mut self) {
+        self.is_suspended = false;
+    }
+
+    /// Captures the game so that it can be restored later, e.g. from a save file.
+    /// The turns that can still be taken back are captured with it.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            undo_history: self.undo_history.clone(),
+            ..self.capture()
+        }
+    }
+
+    /// Captures the game without the turns that can be taken back.
+    fn capture(&self) -> Snapshot {
+        Snapshot {
+            state: self.state,
+            players: self
+                .actors
+                .iter()
+                .map(|actor| actor.get_player().clone())
+                .collect(),
+            actor_states: self.actors.iter().map(|actor| actor.save_state()).collect(),
+            deck: self.deck.clone(),
+            actor_index: self.actor_index,
+            is_flow_clockwise: self.is_flow_clockwise,
+            dealer: self.dealer,
+            rules: self.rules.clone(),
+            pending_penalty: self.pending_penalty,
+            challenge: self.challenge.clone(),
+            uno_declared: self.uno_declared,
+            hands_exchanged: self.hands_exchanged,
+            has_played_card: self.has_played_card,
+            skip_next_actor: self.skip_next_actor,
+            chosen_colour: self.chosen_colour,
+            history: self.history.to_vec(),
+            num_of_turns: self.num_of_turns,
+            num_of_refills: self.num_of_refills,
+            undo_history: Vec::new(),
+        }
+    }
+
+    /// Puts the game back in the position captured by `snapshot`, including the hands,
+    /// the internal state of the actors and the turns that can be taken back. Fails if
+    /// the snapshot was taken with a different number of actors, refers to a player who
+    /// is not in the game, has no card on the discard pile, or an actor cannot restore
+    /// its state. The game is left unchanged if the snapshot is rejected before the
+    /// actors restore their state.
+    pub fn restore(&mut self, snapshot: Snapshot) -> Result<(), String> {
+        let Snapshot {
+            state,
+            players,
+            actor_states,
+            deck,
+            actor_index,
+            is_flow_clockwise,
+            dealer,
+            rules,
+            pending_penalty,
+            challenge,
+            uno_declared,
+            hands_exchanged,
+            has_played_card,
+            skip_next_actor,
+            chosen_colour,
+            history,
+            num_of_turns,
+            num_of_refills,
+            undo_history,
+        } = snapshot;
+        let num_of_actors = self.actors.len();
+        if players.len() != num_of_actors {
+            return Err(format!(
+                "The game was saved with {} players, not {num_of_actors}",
+                players.len()
+            ));
+        }
+        if actor_states.len() != num_of_actors {
+            return Err(format!(
+                "The game was saved with the state of {} players, not {num_of_actors}",
+                actor_states.len()
+            ));
+        }
+        if actor_index >= num_of_actors || dealer >= num_of_actors {
+            return Err(format!(
+                "The game was saved with player {actor_index} to play and player {dealer} \
+                 dealing, but only has {num_of_actors} players"
+            ));
+        }
+        if deck.get_top_card().is_err() {
+            return Err(String::from(
+                "The game was saved with no card on the discard pile",
+            ));
+        }
+        if let Some(challenge) = &challenge {
+            if challenge.offender >= num_of_actors || challenge.victim >= num_of_actors {
+                return Err(format!(
+                    "The game was saved with a challenge between players {} and {}, but only \
+                     has {num_of_actors} players",
+                    challenge.offender, challenge.victim
+                ));
+            }
+        }
+        for ((actor, player), actor_state) in self.actors.iter_mut().zip(players).zip(actor_states)
+        {
+            *actor.get_player_mut() = player;
+            actor.load_state(actor_state)?;
+        }
+        self.state = state;
+        self.deck = deck;
+        self.actor_index = actor_index;
+        self.is_flow_clockwise = is_flow_clockwise;
+        self.dealer = dealer;
+        self.rules = rules;
+        self.pending_penalty = pending_penalty;
+        self.challenge = challenge;
+        self.uno_declared = uno_declared;
+        self.hands_exchanged = hands_exchanged;
+        self.has_played_card = has_played_card;
+        self.skip_next_actor = skip_next_actor;
+        self.chosen_colour = chosen_colour;
+        self.history = Rc::new(history);
+        self.num_of_turns = num_of_turns;
+        self.num_of_refills = num_of_refills;
+        self.undo_history = undo_history;
+        self.is_suspended = false;
+        Ok(())
+    }
+
+    /// Creates a game in the position captured by `snapshot`, played by `actors`.
+    pub fn from_snapshot(snapshot: Snapshot, actors: Vec<GameActor>) -> Result<Self, String> {
+        let mut game = Game::new(actors, snapshot.rules.clone(), None);
+        game.restore(snapshot)?;
+        Ok(game)
+    }
+
+    /// Creates a game that continues from a position in the middle of a round,
+    /// with the actor at `actor_index` about to take their turn. Fails if the discard
+    /// pile is empty, as there is no card to play on.
+    pub fn from_position(
+        actors: Vec<GameActor>,
+        deck: deck::Deck,
+        rules: rules::Rules,
+        actor_index: usize,
+        is_flow_clockwise: bool,
+    ) -> Result<Self, String> {
+        if deck.get_top_card().is_err() {
+            return Err(String::from("The position has no card on the discard pile"));
+        }
+        let dealer = actors.len() - 1;
+        Ok(Game {
+            state: flow::GameState::GetPlayerAction,
+            actors,
+            deck,
+            actor_index,
+            is_flow_clockwise,
+            dealer,
+            rules,
+            pending_penalty: 0,
+            challenge: None,
+            uno_declared: false,
+            hands_exchanged: false,
+            has_played_card: false,
+            skip_next_actor: false,
+            chosen_colour: None,
+            history: Rc::default(),
+            num_of_turns: 0,
+            num_of_refills: 0,
+            observers: Vec::new(),
+            is_suspended: false,
+            error: None,
+            undo_actors: Vec::new(),
+            undo_history: Vec::new(),
+        })
+    }
+
+    pub fn new(actors: Vec<GameActor>, rules: rules::Rules, seed: Option<u64>) -> Self {
+        let dealer = actors.len() - 1;
         Game {
             state: flow::GameState::Init,
             actors,
-            deck: deck::Deck::new(None),
+            deck: deck::Deck::new(None, seed),
             actor_index: 0,
             is_flow_clockwise: true,
-            num_of_cards,
+            dealer,
+            rules,
+            pending_penalty: 0,
+            challenge: None,
+            uno_declared: false,
+            hands_exchanged: false,
+            has_played_card: false,
+            skip_next_actor: false,
+            chosen_colour: None,
+            history: Rc::default(),
+            num_of_turns: 0,
+            num_of_refills: 0,
+            observers: Vec::new(),
+            is_suspended: false,
+            error: None,
+            undo_actors: Vec::new(),
+            undo_history: Vec::new(),
         }
     }
 }
 
+/// Creates the AI actor with ID `id`. Every AI gets its own seed derived from `seed`.
+pub fn create_ai_actor(
+    id: usize,
+    difficulty: strategy::Difficulty,
+    rules: &rules::Rules,
+    search_budget: mcts::SearchBudget,
+    seed: Option<u64>,
+) -> GameActor {
+    Box::new(new_ai_actor(id, difficulty, rules, search_budget, seed))
+}
+
+/// Creates the AI actor with ID `id`, unboxed so that it can be configured further.
+pub fn new_ai_actor(
+    id: usize,
+    difficulty: strategy::Difficulty,
+    rules: &rules::Rules,
+    search_budget: mcts::SearchBudget,
+    seed: Option<u64>,
+) -> ai::AiActor {
+    let ai_seed = seed.map(|s| s.wrapping_add(id as u64));
+    let strategy = difficulty.strategy(rules, search_budget);
+    ai::AiActor::new(id, strategy, ai_seed)
+}
+
+/// Starts the external bot with ID `id` running `command`.
+pub fn create_bot_actor(
+    id: usize,
+    command: &str,
+    timeout: Duration,
+    seed: Option<u64>,
+) -> Result<GameActor, String> {
+    let bot_seed = seed.map(|s| s.wrapping_add(id as u64));
+    Ok(Box::new(bot::BotActor::new(
+        id, command, timeout, bot_seed,
+    )?))
+}
+
 impl flow::GameFlow for Game {
     fn get_state(&self) -> flow::GameState {
         self.state
@@ -260,25 +1137,47 @@ impl flow::GameFlow for Game {
         self.state = state;
     }
 
+    fn is_suspended(&self) -> bool {
+        self.is_suspended
+    }
+
     fn handle_init(&mut self) -> flow::GameState {
         self.deal_cards_to_players();
+        self.apply_starting_card();
         flow::GameState::TurnStarts
     }
 
     fn handle_turn_start(&mut self) -> flow::GameState {
-        ui::get_game_context(self.actor_index, &self.deck);
+        if self.undo_actors.contains(&self.actor_index) {
+            let snapshot = self.capture();
+            self.undo_history.push((self.actor_index, snapshot));
+        }
         self.get_current_actor_mut().pre_turn_action();
-        flow::GameState::GetPlayerAction
+        if self.pending_penalty > 0 {
+            flow::GameState::DecideStack
+        } else {
+            flow::GameState::GetPlayerAction
+        }
     }
 
     fn handle_get_player_action(&mut self) -> flow::GameState {
-        let action = self.get_current_actor_mut().get_turn_action();
+        let legal_actions = self.legal_actions(self.actor_index);
+        let observation = self.observe(self.actor_index);
+        let action = self
+            .get_current_actor_mut()
+            .get_turn_action(&legal_actions, &observation);
+        if let Some(state) = self.run_command(self.actor_index) {
+            return state;
+        }
         match self.get_player_action(self.get_current_actor().get_player(), action) {
-            Ok(GameAction::PlayerDraw) => {
-                flow::GameState::ExecutePlayerAction(GameAction::PlayerDraw)
-            }
-            Ok(GameAction::PlayerPlaysCard(i)) => {
-                flow::GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(i))
+            Ok(
+                action @ (GameAction::PlayerDraw
+                | GameAction::PlayerPlaysCard(_)
+                | GameAction::PlayerPlaysWild(..)),
+            ) => flow::GameState::ExecutePlayerAction(action),
+            Ok(GameAction::DeclareUno) => {
+                self.declare_uno();
+                flow::GameState::GetPlayerAction
             }
             _ => flow::GameState::GetPlayerAction,
         }
@@ -289,14 +1188,113 @@ impl flow::GameFlow for Game {
         let action = self.execute_player_action(actor.get_id(), action);
         match action {
             Ok(GameAction::ChooseColour) => flow::GameState::ChooseColour,
+            Ok(GameAction::SwapHands) => flow::GameState::SwapHands,
+            Ok(GameAction::PlayerDraw) if self.has_drawn_valid_play(self.actor_index) => {
+                flow::GameState::PlayDrawnCard
+            }
             _ => flow::GameState::EndTurn,
         }
     }
 
+    fn handle_play_drawn_card(&mut self) -> flow::GameState {
+        let legal_actions = self.legal_actions(self.actor_index);
+        let observation = self.observe(self.actor_index);
+        let action = self
+            .get_current_actor_mut()
+            .get_drawn_card_action(&legal_actions, &observation);
+        if let Some(state) = self.run_command(self.actor_index) {
+            return state;
+        }
+        match self.get_player_drawn_card_action(self.get_current_actor().get_player(), action) {
+            Ok(action @ (GameAction::PlayerPlaysCard(_) | GameAction::PlayerPlaysWild(..))) => {
+                flow::GameState::ExecutePlayerAction(action)
+            }
+            Ok(GameAction::DeclareUno) => {
+                self.declare_uno();
+                flow::GameState::PlayDrawnCard
+            }
+            Ok(GameAction::None) => flow::GameState::EndTurn,
+            _ => flow::GameState::PlayDrawnCard,
+        }
+    }
+
     fn handle_choose_colour(&mut self) -> flow::GameState {
-        let actor = self.get_current_actor_mut();
-        let colour = actor.get_color_choice();
+        let colour = match self.chosen_colour.take() {
+            Some(colour) => colour,
+            None => {
+                let observation = self.observe(self.actor_index);
+                let colour = self.get_current_actor_mut().get_color_choice(&observation);
+                if let Some(state) = self.run_command(self.actor_index) {
+                    return state;
+                }
+                colour
+            }
+        };
         self.change_wild_color(&colour);
+        if self.challenge.is_some() {
+            flow::GameState::ChallengeWildDraw
+        } else {
+            flow::GameState::EndTurn
+        }
+    }
+
+    fn handle_swap_hands(&mut self) -> flow::GameState {
+        let hand_sizes: Vec<usize> = self
+            .actors
+            .iter()
+            .map(|actor| actor.get_player().get_number_of_cards())
+            .collect();
+        let target = self.get_current_actor_mut().get_swap_target(&hand_sizes);
+        if let Some(state) = self.run_command(self.actor_index) {
+            return state;
+        }
+        if target == self.actor_index || target >= self.actors.len() {
+            return flow::GameState::SwapHands;
+        }
+        self.swap_hands(self.actor_index, target);
+        flow::GameState::EndTurn
+    }
+
+    fn handle_decide_stack(&mut self) -> flow::GameState {
+        let penalty = self.pending_penalty;
+        let legal_actions = self.legal_actions(self.actor_index);
+        let observation = self.observe(self.actor_index);
+        let action =
+            self.get_current_actor_mut()
+                .get_stack_action(penalty, &legal_actions, &observation);
+        if let Some(state) = self.run_command(self.actor_index) {
+            return state;
+        }
+        match self.get_player_stack_action(self.get_current_actor().get_player(), action) {
+            Ok(action @ (GameAction::PlayerPlaysCard(_) | GameAction::PlayerPlaysWild(..))) => {
+                flow::GameState::ExecutePlayerAction(action)
+            }
+            Ok(GameAction::PlayerDraw) => flow::GameState::DrawPenalty,
+            Ok(GameAction::DeclareUno) => {
+                self.declare_uno();
+                flow::GameState::DecideStack
+            }
+            _ => flow::GameState::DecideStack,
+        }
+    }
+
+    fn handle_draw_penalty(&mut self) -> flow::GameState {
+        let penalty = std::mem::take(&mut self.pending_penalty);
+        self.apply_penalty(self.actor_index, penalty);
+        flow::GameState::EndTurn
+    }
+
+    fn handle_challenge_wild_draw(&mut self) -> flow::GameState {
+        let Some(victim) = self.challenge.as_ref().map(|challenge| challenge.victim) else {
+            return flow::GameState::EndTurn;
+        };
+        let is_challenged = self.get_actor_mut(victim).decide_challenge();
+        if let Some(state) = self.run_command(victim) {
+            return state;
+        }
+        if let Some(challenge) = self.challenge.take() {
+            self.resolve_challenge(challenge, is_challenged);
+        }
         flow::GameState::EndTurn
     }
 
@@ -305,14 +1303,1354 @@ impl flow::GameFlow for Game {
         if self.has_player_won(player.get_id()) {
             return flow::GameState::EndGame;
         }
-        self.get_current_actor_mut().post_turn_action();
-        self.set_next_actor();
-        flow::GameState::TurnStarts
+        if self.has_missed_uno(self.actor_index) {
+            return flow::GameState::CatchMissedUno;
+        }
+        self.finish_turn()
+    }
+
+    fn handle_catch_missed_uno(&mut self) -> flow::GameState {
+        if let Err(state) = self.catch_missed_uno(self.actor_index) {
+            return state;
+        }
+        self.finish_turn()
+    }
+
+    fn handle_jump_in(&mut self) -> flow::GameState {
+        let jump_in = match self.find_jump_in() {
+            Ok(jump_in) => jump_in,
+            Err(state) => return state,
+        };
+        match jump_in {
+            Some((jumper_index, card_index)) => {
+                // Play continues from the player who jumped in
+                self.skip_next_actor = false;
+                self.close_turn();
+                self.actor_index = jumper_index;
+                // Jumping in leaves no turn to declare UNO in, so it is declared with the play
+                if self.can_declare_uno(self.get_current_actor().get_player()) {
+                    self.declare_uno();
+                }
+                flow::GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(card_index))
+            }
+            None => self.pass_turn(),
+        }
     }
 
     fn handle_end_game(&mut self) -> flow::GameState {
-        let actor = self.get_current_actor();
-        ui::announce_winner(actor.get_id());
+        if let Some(player) = self.get_winner() {
+            self.emit(event::Event::RoundWon { player });
+        }
         flow::GameState::End
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card, Colour, Value};
+    use crate::flow::{GameFlow, GameState};
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    /// Actor that answers every decision from a pre-recorded script.
+    struct ScriptedActor {
+        id: usize,
+        player: player::Player,
+        actions: VecDeque<actor::UserAction>,
+        /// Command given instead of each move, `None` to make the next scripted move.
+        commands: VecDeque<Option<actor::Command>>,
+        command: Option<actor::Command>,
+        challenges: bool,
+        catches: bool,
+        jumps_in: bool,
+        swap_target: usize,
+    }
+
+    impl ScriptedActor {
+        fn next_action(&mut self, default: actor::UserAction) -> actor::UserAction {
+            self.command = self.commands.pop_front().flatten();
+            if self.command.is_some() {
+                return default;
+            }
+            self.actions.pop_front().unwrap_or(default)
+        }
+
+        fn with_commands(mut self, commands: Vec<Option<actor::Command>>) -> Self {
+            self.commands = VecDeque::from(commands);
+            self
+        }
+    }
+
+    impl actor::Actor for ScriptedActor {
+        fn get_turn_action(
+            &mut self,
+            _legal_actions: &[actor::UserAction],
+            _observation: &observation::Observation,
+        ) -> actor::UserAction {
+            self.next_action(actor::UserAction::Draw)
+        }
+
+        fn get_drawn_card_action(
+            &mut self,
+            _legal_actions: &[actor::UserAction],
+            _observation: &observation::Observation,
+        ) -> actor::UserAction {
+            self.next_action(actor::UserAction::Pass)
+        }
+
+        fn get_stack_action(
+            &mut self,
+            _penalty: usize,
+            _legal_actions: &[actor::UserAction],
+            _observation: &observation::Observation,
+        ) -> actor::UserAction {
+            self.next_action(actor::UserAction::Draw)
+        }
+
+        fn decide_challenge(&mut self) -> bool {
+            self.challenges
+        }
+
+        fn catch_missed_uno(&mut self, _offender_id: usize) -> bool {
+            self.catches
+        }
+
+        fn decide_jump_in(&mut self, _card_index: usize) -> bool {
+            self.jumps_in
+        }
+
+        fn get_swap_target(&mut self, _hand_sizes: &[usize]) -> usize {
+            self.swap_target
+        }
+
+        fn get_color_choice(&mut self, _observation: &observation::Observation) -> card::Colour {
+            card::Colour::Red
+        }
+
+        fn pre_turn_action(&mut self) {}
+
+        fn post_turn_action(&mut self) {}
+
+        fn take_command(&mut self) -> Option<actor::Command> {
+            self.command.take()
+        }
+
+        fn get_player(&self) -> &player::Player {
+            &self.player
+        }
+
+        fn get_player_mut(&mut self) -> &mut player::Player {
+            &mut self.player
+        }
+
+        fn get_id(&self) -> usize {
+            self.id
+        }
+    }
+
+    fn card(colour: Colour, value: Value) -> Card {
+        Card { colour, value }
+    }
+
+    fn scripted_actor(
+        id: usize,
+        hand: Vec<Card>,
+        actions: Vec<actor::UserAction>,
+    ) -> ScriptedActor {
+        let mut player = player::Player::new();
+        hand.into_iter().for_each(|c| player.take_card(c));
+        ScriptedActor {
+            id,
+            player,
+            actions: VecDeque::from(actions),
+            commands: VecDeque::new(),
+            command: None,
+            challenges: false,
+            catches: false,
+            jumps_in: false,
+            swap_target: 0,
+        }
+    }
+
+    fn create_test_game(actors: Vec<ScriptedActor>) -> Game {
+        let actors = actors
+            .into_iter()
+            .map(|actor| Box::new(actor) as GameActor)
+            .collect();
+        let draw_pile = vec![card(Colour::Blue, Value::Number(1)); 20];
+        Game {
+            state: flow::GameState::GetPlayerAction,
+            actors,
+            deck: deck::Deck::new(Some(draw_pile), Some(0)),
+            actor_index: 0,
+            is_flow_clockwise: true,
+            dealer: 1,
+            rules: rules::Rules {
+                starting_hand_size: 0,
+                ..rules::Rules::default()
+            },
+            pending_penalty: 0,
+            challenge: None,
+            uno_declared: false,
+            hands_exchanged: false,
+            has_played_card: false,
+            skip_next_actor: false,
+            chosen_colour: None,
+            history: Rc::default(),
+            num_of_turns: 0,
+            num_of_refills: 0,
+            observers: Vec::new(),
+            is_suspended: false,
+            error: None,
+            undo_actors: Vec::new(),
+            undo_history: Vec::new(),
+        }
+    }
+
+    fn hand_size(game: &Game, index: usize) -> usize {
+        game.get_actor(index).get_player().get_number_of_cards()
+    }
+
+    #[test]
+    fn test_draw_two_without_stacking_draws_immediately() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Blue, Value::DrawTwo)], vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        assert_eq!(hand_size(&game, 1), 2);
+        assert_eq!(game.pending_penalty, 0);
+    }
+
+    #[test]
+    fn test_stacking_player_can_declare_uno() {
+        let mut catcher = scripted_actor(0, vec![card(Colour::Blue, Value::Number(1))], vec![]);
+        catcher.catches = true;
+        let mut game = create_test_game(vec![
+            catcher,
+            scripted_actor(
+                1,
+                vec![
+                    card(Colour::Red, Value::DrawTwo),
+                    card(Colour::Red, Value::Number(5)),
+                ],
+                vec![actor::UserAction::Uno, actor::UserAction::Play(0)],
+            ),
+        ]);
+        game.rules.stacking = true;
+        game.pending_penalty = 2;
+        game.actor_index = 1;
+        game.set_state(GameState::DecideStack);
+        assert!(game.legal_actions(1).contains(&actor::UserAction::Uno));
+        game.run_game_phase(GameState::DecideStack);
+        assert!(matches!(game.get_state(), GameState::DecideStack));
+        while !matches!(game.get_state(), GameState::EndTurn) {
+            game.run_game_phase(game.get_state());
+        }
+        game.run_game_phase(GameState::EndTurn);
+        assert!(!matches!(game.get_state(), GameState::CatchMissedUno));
+        assert_eq!(hand_size(&game, 1), 1);
+        assert_eq!(game.pending_penalty, 4);
+    }
+
+    #[test]
+    fn test_draw_two_with_stacking_accumulates_penalty() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Blue, Value::DrawTwo)], vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.rules.stacking = true;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        assert_eq!(hand_size(&game, 1), 0);
+        assert_eq!(game.pending_penalty, 2);
+    }
+
+    #[test]
+    fn test_stacking_passes_penalty_on() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![], vec![]),
+            scripted_actor(
+                1,
+                vec![card(Colour::Red, Value::DrawTwo)],
+                vec![actor::UserAction::Play(0)],
+            ),
+        ]);
+        game.rules.stacking = true;
+        game.pending_penalty = 2;
+        game.actor_index = 1;
+        game.run_game_phase(GameState::DecideStack);
+        assert!(matches!(
+            game.get_state(),
+            GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(0))
+        ));
+        game.run_game_phase(game.get_state());
+        assert_eq!(game.pending_penalty, 4);
+        assert_eq!(hand_size(&game, 1), 0);
+    }
+
+    #[test]
+    fn test_stacking_rejects_non_draw_card() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![], vec![]),
+            scripted_actor(
+                1,
+                vec![card(Colour::Red, Value::Skip)],
+                vec![actor::UserAction::Play(0)],
+            ),
+        ]);
+        game.rules.stacking = true;
+        game.pending_penalty = 2;
+        game.actor_index = 1;
+        game.run_game_phase(GameState::DecideStack);
+        assert!(matches!(game.get_state(), GameState::DecideStack));
+    }
+
+    #[test]
+    fn test_declining_stack_draws_total_penalty() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![], vec![]),
+            scripted_actor(
+                1,
+                vec![card(Colour::Red, Value::DrawTwo)],
+                vec![actor::UserAction::Draw],
+            ),
+        ]);
+        game.rules.stacking = true;
+        game.pending_penalty = 6;
+        game.actor_index = 1;
+        game.run_game_phase(GameState::DecideStack);
+        assert!(matches!(game.get_state(), GameState::DrawPenalty));
+        game.run_game_phase(game.get_state());
+        assert!(matches!(game.get_state(), GameState::EndTurn));
+        assert_eq!(hand_size(&game, 1), 7);
+        assert_eq!(game.pending_penalty, 0);
+    }
+
+    fn play_wild_draw(offender_hand: Vec<Card>, challenges: bool) -> Game {
+        let mut victim = scripted_actor(1, vec![], vec![]);
+        victim.challenges = challenges;
+        let mut game = create_test_game(vec![scripted_actor(0, offender_hand, vec![]), victim]);
+        game.deck.discard(card(Colour::Green, Value::Number(3)));
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        assert!(matches!(game.get_state(), GameState::ChooseColour));
+        game.run_game_phase(GameState::ChooseColour);
+        assert!(matches!(game.get_state(), GameState::ChallengeWildDraw));
+        game.run_game_phase(GameState::ChallengeWildDraw);
+        assert!(matches!(game.get_state(), GameState::EndTurn));
+        game
+    }
+
+    #[test]
+    fn test_wild_draw_unchallenged_victim_draws() {
+        let hand = vec![
+            card(Colour::Wild, Value::WildDraw(4)),
+            card(Colour::Green, Value::Number(5)),
+        ];
+        let game = play_wild_draw(hand, false);
+        assert_eq!(hand_size(&game, 0), 1);
+        assert_eq!(hand_size(&game, 1), 4);
+    }
+
+    #[test]
+    fn test_wild_draw_challenge_succeeds_offender_draws() {
+        let hand = vec![
+            card(Colour::Wild, Value::WildDraw(4)),
+            card(Colour::Green, Value::Number(5)),
+        ];
+        let game = play_wild_draw(hand, true);
+        assert_eq!(hand_size(&game, 0), 5);
+        assert_eq!(hand_size(&game, 1), 0);
+    }
+
+    #[test]
+    fn test_wild_draw_challenge_fails_challenger_draws_six() {
+        let hand = vec![
+            card(Colour::Wild, Value::WildDraw(4)),
+            card(Colour::Blue, Value::Number(3)),
+        ];
+        let game = play_wild_draw(hand, true);
+        assert_eq!(hand_size(&game, 0), 1);
+        assert_eq!(hand_size(&game, 1), 6);
+    }
+
+    fn play_second_to_last_card(actions: Vec<actor::UserAction>, catches: bool) -> Game {
+        let hand = vec![
+            card(Colour::Blue, Value::Number(2)),
+            card(Colour::Blue, Value::Number(3)),
+        ];
+        let mut catcher = scripted_actor(1, vec![card(Colour::Red, Value::Number(1))], vec![]);
+        catcher.catches = catches;
+        let mut game = create_test_game(vec![scripted_actor(0, hand, actions), catcher]);
+        while !matches!(game.get_state(), GameState::EndTurn) {
+            game.run_game_phase(game.get_state());
+        }
+        game.run_game_phase(GameState::EndTurn);
+        game
+    }
+
+    #[test]
+    fn test_declared_uno_is_not_caught() {
+        let actions = vec![actor::UserAction::Uno, actor::UserAction::Play(0)];
+        let game = play_second_to_last_card(actions, true);
+        assert!(matches!(game.get_state(), GameState::TurnStarts));
+        assert_eq!(hand_size(&game, 0), 1);
+        assert!(!game.uno_declared);
+    }
+
+    #[test]
+    fn test_missed_uno_is_caught() {
+        let mut game = play_second_to_last_card(vec![actor::UserAction::Play(0)], true);
+        assert!(matches!(game.get_state(), GameState::CatchMissedUno));
+        game.run_game_phase(GameState::CatchMissedUno);
+        assert!(matches!(game.get_state(), GameState::TurnStarts));
+        assert_eq!(hand_size(&game, 0), 3);
+    }
+
+    #[test]
+    fn test_missed_uno_is_not_caught() {
+        let mut game = play_second_to_last_card(vec![actor::UserAction::Play(0)], false);
+        game.run_game_phase(GameState::CatchMissedUno);
+        assert_eq!(hand_size(&game, 0), 1);
+    }
+
+    fn start_with(top_card: Card) -> Game {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![], vec![]),
+            scripted_actor(1, vec![], vec![]),
+            scripted_actor(2, vec![], vec![]),
+        ]);
+        game.set_dealer(2);
+        game.deck.discard(top_card);
+        game.run_game_phase(GameState::Init);
+        assert!(matches!(game.get_state(), GameState::TurnStarts));
+        game
+    }
+
+    #[test]
+    fn test_starting_number_has_no_effect() {
+        let game = start_with(card(Colour::Red, Value::Number(4)));
+        assert_eq!(game.actor_index, 0);
+        assert!(game.is_flow_clockwise);
+    }
+
+    #[test]
+    fn test_starting_skip_skips_first_player() {
+        let game = start_with(card(Colour::Red, Value::Skip));
+        assert_eq!(game.actor_index, 1);
+    }
+
+    #[test]
+    fn test_starting_reverse_dealer_plays_first() {
+        let game = start_with(card(Colour::Red, Value::Reverse));
+        assert_eq!(game.actor_index, 2);
+        assert!(!game.is_flow_clockwise);
+    }
+
+    #[test]
+    fn test_starting_draw_two_first_player_draws_and_is_skipped() {
+        let game = start_with(card(Colour::Red, Value::DrawTwo));
+        assert_eq!(hand_size(&game, 0), 2);
+        assert_eq!(game.actor_index, 1);
+    }
+
+    #[test]
+    fn test_starting_wild_first_player_chooses_colour() {
+        let game = start_with(card(Colour::Wild, Value::Wild));
+        assert_eq!(game.deck.get_top_card().unwrap().colour, Colour::Wild);
+        assert_eq!(game.deck.get_current_colour(), Ok(Colour::Red));
+        assert_eq!(game.actor_index, 0);
+    }
+
+    #[test]
+    fn test_starting_wild_draw_is_replaced() {
+        let game = start_with(card(Colour::Wild, Value::WildDraw(4)));
+        let top_card = game.deck.get_top_card().unwrap();
+        assert_ne!(top_card.value, Value::WildDraw(4));
+        assert_eq!(game.deck.number_of_cards_in_draw_pile(), 19);
+    }
+
+    #[test]
+    fn test_valid_play_follows_declared_colour() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![], vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.deck.discard(card(Colour::Wild, Value::Wild));
+        game.change_wild_color(&Colour::Green);
+        assert!(game.is_valid_play(&card(Colour::Green, Value::Number(1))));
+        assert!(game.is_valid_play(&card(Colour::Wild, Value::WildDraw(4))));
+        assert!(!game.is_valid_play(&card(Colour::Red, Value::Number(1))));
+    }
+
+    #[test]
+    fn test_winning_with_skip_ends_game() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Blue, Value::Skip)], vec![]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1))], vec![]),
+            scripted_actor(2, vec![card(Colour::Red, Value::Number(1))], vec![]),
+        ]);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::EndTurn);
+        assert!(matches!(game.get_state(), GameState::EndGame));
+        assert_eq!(game.get_winner(), Some(0));
+    }
+
+    #[test]
+    fn test_skip_skips_next_player() {
+        let mut game = create_test_game(vec![
+            scripted_actor(
+                0,
+                vec![
+                    card(Colour::Blue, Value::Skip),
+                    card(Colour::Red, Value::Number(1)),
+                    card(Colour::Red, Value::Number(2)),
+                ],
+                vec![],
+            ),
+            scripted_actor(1, vec![], vec![]),
+            scripted_actor(2, vec![], vec![]),
+        ]);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::EndTurn);
+        assert_eq!(game.actor_index, 2);
+    }
+
+    #[test]
+    fn test_forced_play_rejects_draw_with_valid_card() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Blue, Value::Number(5))], vec![]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(5))], vec![]),
+        ]);
+        game.rules.forced_play = true;
+        let draw = game.get_player_action(game.get_actor(0).get_player(), actor::UserAction::Draw);
+        assert!(matches!(draw, Err(Error::InvalidPlay)));
+        let draw = game.get_player_action(game.get_actor(1).get_player(), actor::UserAction::Draw);
+        assert!(matches!(draw, Ok(GameAction::PlayerDraw)));
+    }
+
+    #[test]
+    fn test_draw_until_playable() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![], vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.rules.draw_until_playable = true;
+        game.deck.discard(card(Colour::Green, Value::Number(2)));
+        for c in [
+            card(Colour::Blue, Value::Number(2)),
+            card(Colour::Red, Value::Number(3)),
+            card(Colour::Red, Value::Number(4)),
+        ] {
+            game.deck.discard(c);
+        }
+        game.deck.refill_draw_pile().unwrap();
+        game.deck.discard(card(Colour::Green, Value::Number(9)));
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerDraw));
+        let hand = game.get_actor(0).get_player().get_hand();
+        assert!(game.is_valid_play(hand.last().unwrap()));
+        assert!(hand[..hand.len() - 1]
+            .iter()
+            .all(|c| !game.is_valid_play(c)));
+    }
+
+    #[test]
+    fn test_seven_swaps_hands() {
+        let mut player = scripted_actor(
+            0,
+            vec![
+                card(Colour::Blue, Value::Number(7)),
+                card(Colour::Red, Value::Number(1)),
+            ],
+            vec![],
+        );
+        player.swap_target = 2;
+        let mut game = create_test_game(vec![
+            player,
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(2))], vec![]),
+            scripted_actor(2, vec![card(Colour::Red, Value::Number(3)); 3], vec![]),
+        ]);
+        game.rules.seven_o = true;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        assert!(matches!(game.get_state(), GameState::SwapHands));
+        game.run_game_phase(GameState::SwapHands);
+        assert!(matches!(game.get_state(), GameState::EndTurn));
+        assert_eq!(hand_size(&game, 0), 3);
+        assert_eq!(hand_size(&game, 1), 1);
+        assert_eq!(hand_size(&game, 2), 1);
+        assert_eq!(
+            game.observe(1).history.last(),
+            Some(&observation::PlayRecord::HandsSwapped {
+                player: 0,
+                other: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_seven_rejects_swapping_with_self() {
+        let mut game = create_test_game(vec![
+            scripted_actor(
+                0,
+                vec![
+                    card(Colour::Blue, Value::Number(7)),
+                    card(Colour::Red, Value::Number(1)),
+                ],
+                vec![],
+            ),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.rules.seven_o = true;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::SwapHands);
+        assert!(matches!(game.get_state(), GameState::SwapHands));
+    }
+
+    #[test]
+    fn test_zero_rotates_hands_in_direction_of_play() {
+        let mut game = create_test_game(vec![
+            scripted_actor(
+                0,
+                vec![
+                    card(Colour::Blue, Value::Number(0)),
+                    card(Colour::Red, Value::Number(1)),
+                ],
+                vec![],
+            ),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(2)); 2], vec![]),
+            scripted_actor(2, vec![card(Colour::Red, Value::Number(3)); 3], vec![]),
+        ]);
+        game.rules.seven_o = true;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        assert_eq!(hand_size(&game, 0), 3);
+        assert_eq!(hand_size(&game, 1), 1);
+        assert_eq!(hand_size(&game, 2), 2);
+        assert_eq!(
+            game.observe(1).history.last(),
+            Some(&observation::PlayRecord::HandsRotated)
+        );
+    }
+
+    fn end_turn_after_exchange(mut game: Game) -> Game {
+        game.rules.seven_o = true;
+        game.set_state(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        while !matches!(game.get_state(), GameState::EndTurn) {
+            game.run_game_phase(game.get_state());
+        }
+        game.run_game_phase(GameState::EndTurn);
+        game
+    }
+
+    #[test]
+    fn test_hand_left_with_one_card_by_a_swap_is_not_a_missed_uno() {
+        let mut player = scripted_actor(
+            0,
+            vec![
+                card(Colour::Blue, Value::Number(7)),
+                card(Colour::Red, Value::Number(1)),
+            ],
+            vec![],
+        );
+        player.swap_target = 1;
+        let mut catcher = scripted_actor(1, vec![card(Colour::Red, Value::Number(2))], vec![]);
+        catcher.catches = true;
+        let game = end_turn_after_exchange(create_test_game(vec![player, catcher]));
+        assert!(matches!(game.get_state(), GameState::TurnStarts));
+        assert_eq!(hand_size(&game, 0), 1);
+    }
+
+    #[test]
+    fn test_hand_left_with_one_card_by_a_rotation_is_not_a_missed_uno() {
+        let mut catcher = scripted_actor(1, vec![card(Colour::Red, Value::Number(2)); 2], vec![]);
+        catcher.catches = true;
+        let game = end_turn_after_exchange(create_test_game(vec![
+            scripted_actor(
+                0,
+                vec![
+                    card(Colour::Blue, Value::Number(0)),
+                    card(Colour::Red, Value::Number(1)),
+                ],
+                vec![],
+            ),
+            catcher,
+            scripted_actor(2, vec![card(Colour::Red, Value::Number(3))], vec![]),
+        ]));
+        assert!(matches!(game.get_state(), GameState::TurnStarts));
+        assert_eq!(hand_size(&game, 0), 1);
+    }
+
+    #[test]
+    fn test_jump_in_with_identical_card() {
+        let blue_five = card(Colour::Blue, Value::Number(5));
+        let mut jumper = scripted_actor(2, vec![blue_five; 3], vec![]);
+        jumper.jumps_in = true;
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![blue_five; 3], vec![]),
+            scripted_actor(1, vec![blue_five; 3], vec![]),
+            jumper,
+        ]);
+        game.rules.jump_in = true;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::EndTurn);
+        assert!(matches!(game.get_state(), GameState::JumpIn));
+        game.run_game_phase(GameState::JumpIn);
+        assert_eq!(game.actor_index, 2);
+        game.run_game_phase(game.get_state());
+        assert_eq!(hand_size(&game, 2), 2);
+        game.run_game_phase(GameState::EndTurn);
+        assert!(matches!(game.get_state(), GameState::JumpIn));
+        game.run_game_phase(GameState::JumpIn);
+        assert!(matches!(game.get_state(), GameState::TurnStarts));
+        assert_eq!(game.actor_index, 0);
+    }
+
+    #[test]
+    fn test_jump_in_with_second_to_last_card_declares_uno() {
+        let blue_five = card(Colour::Blue, Value::Number(5));
+        let mut catcher = scripted_actor(0, vec![blue_five; 3], vec![]);
+        catcher.catches = true;
+        let mut jumper = scripted_actor(1, vec![blue_five; 2], vec![]);
+        jumper.jumps_in = true;
+        let mut game = create_test_game(vec![catcher, jumper]);
+        game.rules.jump_in = true;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::EndTurn);
+        game.run_game_phase(GameState::JumpIn);
+        assert_eq!(game.actor_index, 1);
+        game.run_game_phase(game.get_state());
+        game.run_game_phase(GameState::EndTurn);
+        assert!(!matches!(game.get_state(), GameState::CatchMissedUno));
+        assert_eq!(hand_size(&game, 1), 1);
+    }
+
+    /// Runs the current player's turn from their action until the next turn starts.
+    fn play_turn(game: &mut Game) {
+        game.set_state(GameState::GetPlayerAction);
+        while !matches!(game.get_state(), GameState::TurnStarts | GameState::EndGame) {
+            game.run_game_phase(game.get_state());
+        }
+    }
+
+    fn reverse_game(num_of_players: usize) -> Game {
+        let hand = vec![
+            card(Colour::Blue, Value::Reverse),
+            card(Colour::Blue, Value::Number(2)),
+            card(Colour::Blue, Value::Number(3)),
+        ];
+        let actions = vec![actor::UserAction::Play(0), actor::UserAction::Play(0)];
+        let mut actors = vec![scripted_actor(0, hand, actions)];
+        actors.extend((1..num_of_players).map(|id| {
+            let hand = vec![card(Colour::Blue, Value::Number(4)); 3];
+            scripted_actor(id, hand, vec![actor::UserAction::Play(0)])
+        }));
+        create_test_game(actors)
+    }
+
+    #[test]
+    fn test_two_player_reverse_gives_another_turn() {
+        let mut game = reverse_game(2);
+        play_turn(&mut game);
+        assert_eq!(game.actor_index, 0);
+        assert!(game.is_flow_clockwise);
+        play_turn(&mut game);
+        assert_eq!(hand_size(&game, 0), 1);
+        assert_eq!(game.actor_index, 1);
+    }
+
+    #[test]
+    fn test_two_player_reverse_without_rule_passes_turn() {
+        let mut game = reverse_game(2);
+        game.rules.two_player_reverse_skips = false;
+        play_turn(&mut game);
+        assert_eq!(game.actor_index, 1);
+        assert!(!game.is_flow_clockwise);
+    }
+
+    #[test]
+    fn test_three_player_reverse_changes_direction() {
+        let mut game = reverse_game(3);
+        play_turn(&mut game);
+        assert_eq!(game.actor_index, 2);
+        assert!(!game.is_flow_clockwise);
+        play_turn(&mut game);
+        assert_eq!(game.actor_index, 1);
+    }
+
+    fn draw_card_onto(top_card: Card, actions: Vec<actor::UserAction>) -> Game {
+        let hand = vec![card(Colour::Red, Value::Number(8)); 2];
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hand, actions),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.deck.discard(top_card);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerDraw));
+        game
+    }
+
+    #[test]
+    fn test_drawn_playable_card_can_be_played() {
+        let mut game = draw_card_onto(
+            card(Colour::Blue, Value::Number(4)),
+            vec![actor::UserAction::Play(2)],
+        );
+        assert!(matches!(game.get_state(), GameState::PlayDrawnCard));
+        game.run_game_phase(GameState::PlayDrawnCard);
+        assert!(matches!(
+            game.get_state(),
+            GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(2))
+        ));
+        game.run_game_phase(game.get_state());
+        assert_eq!(hand_size(&game, 0), 2);
+    }
+
+    #[test]
+    fn test_drawn_playable_card_can_be_kept() {
+        let mut game = draw_card_onto(
+            card(Colour::Blue, Value::Number(4)),
+            vec![actor::UserAction::Pass],
+        );
+        game.run_game_phase(GameState::PlayDrawnCard);
+        assert!(matches!(game.get_state(), GameState::EndTurn));
+        assert_eq!(hand_size(&game, 0), 3);
+    }
+
+    #[test]
+    fn test_only_drawn_card_can_be_played() {
+        let mut game = draw_card_onto(
+            card(Colour::Red, Value::Number(1)),
+            vec![actor::UserAction::Play(0)],
+        );
+        assert!(matches!(game.get_state(), GameState::PlayDrawnCard));
+        game.run_game_phase(GameState::PlayDrawnCard);
+        assert!(matches!(game.get_state(), GameState::PlayDrawnCard));
+    }
+
+    #[test]
+    fn test_drawn_unplayable_card_ends_turn() {
+        let game = draw_card_onto(card(Colour::Green, Value::Number(4)), vec![]);
+        assert!(matches!(game.get_state(), GameState::EndTurn));
+    }
+
+    fn legal_actions_with_hand(hand: Vec<Card>) -> Vec<actor::UserAction> {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hand, vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.deck.discard(card(Colour::Red, Value::Number(5)));
+        game.legal_actions(0)
+    }
+
+    #[test]
+    fn test_legal_actions_lists_playable_cards_and_draw() {
+        let actions = legal_actions_with_hand(vec![
+            card(Colour::Blue, Value::Number(1)),
+            card(Colour::Red, Value::Number(2)),
+            card(Colour::Green, Value::Number(5)),
+        ]);
+        assert_eq!(
+            actions,
+            vec![
+                actor::UserAction::Play(1),
+                actor::UserAction::Play(2),
+                actor::UserAction::Draw,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legal_actions_lists_wild_colours() {
+        let actions = legal_actions_with_hand(vec![
+            card(Colour::Wild, Value::Wild),
+            card(Colour::Blue, Value::Number(1)),
+            card(Colour::Blue, Value::Number(2)),
+        ]);
+        let expected: Vec<actor::UserAction> = card::COLOURS
+            .iter()
+            .map(|colour| actor::UserAction::PlayWild(0, *colour))
+            .chain([actor::UserAction::Draw])
+            .collect();
+        assert_eq!(actions, expected);
+    }
+
+    #[test]
+    fn test_legal_actions_allows_uno_with_two_cards() {
+        let actions = legal_actions_with_hand(vec![
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Blue, Value::Number(2)),
+        ]);
+        assert!(actions.contains(&actor::UserAction::Uno));
+        let actions = legal_actions_with_hand(vec![card(Colour::Red, Value::Number(1)); 3]);
+        assert!(!actions.contains(&actor::UserAction::Uno));
+    }
+
+    #[test]
+    fn test_legal_actions_with_forced_play_excludes_draw() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Red, Value::Number(1)); 3], vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.rules.forced_play = true;
+        game.deck.discard(card(Colour::Red, Value::Number(5)));
+        assert!(!game.legal_actions(0).contains(&actor::UserAction::Draw));
+    }
+
+    #[test]
+    fn test_legal_actions_when_stacking_lists_draw_cards() {
+        let hand = vec![
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Green, Value::DrawTwo),
+            card(Colour::Wild, Value::WildDraw(4)),
+        ];
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hand, vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.deck.discard(card(Colour::Red, Value::DrawTwo));
+        game.pending_penalty = 2;
+        game.state = GameState::DecideStack;
+        let actions = game.legal_actions(0);
+        assert!(!actions.contains(&actor::UserAction::Play(0)));
+        assert!(actions.contains(&actor::UserAction::Play(1)));
+        assert!(actions.contains(&actor::UserAction::PlayWild(2, Colour::Blue)));
+        assert!(actions.contains(&actor::UserAction::Draw));
+    }
+
+    #[test]
+    fn test_play_wild_declares_chosen_colour() {
+        let hand = vec![
+            card(Colour::Wild, Value::Wild),
+            card(Colour::Blue, Value::Number(1)),
+            card(Colour::Blue, Value::Number(2)),
+        ];
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hand, vec![actor::UserAction::PlayWild(0, Colour::Green)]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        game.deck.discard(card(Colour::Red, Value::Number(5)));
+        game.run_game_phase(GameState::GetPlayerAction);
+        game.run_game_phase(game.get_state());
+        assert!(matches!(game.get_state(), GameState::ChooseColour));
+        game.run_game_phase(GameState::ChooseColour);
+        assert_eq!(game.deck.get_current_colour(), Ok(Colour::Green));
+    }
+
+    #[test]
+    fn test_observation_lists_opponents_in_turn_order() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Red, Value::Number(1)); 1], vec![]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1)); 2], vec![]),
+            scripted_actor(2, vec![card(Colour::Red, Value::Number(1)); 3], vec![]),
+        ]);
+        let observation = game.observe(0);
+        assert_eq!(observation.opponent_ids, vec![1, 2]);
+        assert_eq!(observation.opponent_hand_sizes, vec![2, 3]);
+        game.revese_direction();
+        let observation = game.observe(0);
+        assert_eq!(observation.opponent_ids, vec![2, 1]);
+        assert_eq!(observation.opponent_hand_sizes, vec![3, 2]);
+    }
+
+    #[test]
+    fn test_observation_records_plays_draws_and_colours() {
+        let hand = vec![
+            card(Colour::Wild, Value::Wild),
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Red, Value::Number(2)),
+        ];
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hand, vec![actor::UserAction::PlayWild(0, Colour::Green)]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1)); 4], vec![]),
+        ]);
+        game.run_game_phase(GameState::GetPlayerAction);
+        game.run_game_phase(game.get_state());
+        game.run_game_phase(GameState::ChooseColour);
+        game.actor_index = 1;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerDraw));
+
+        let observation = game.observe(0);
+        assert_eq!(observation.top_card, card(Colour::Wild, Value::Wild));
+        assert_eq!(observation.declared_colour, Some(Colour::Green));
+        assert_eq!(observation.opponent_hand_sizes, vec![5]);
+        assert_eq!(observation.draw_pile_size, 18);
+        assert_eq!(
+            *observation.history,
+            vec![
+                observation::PlayRecord::CardPlayed {
+                    player: 0,
+                    card: card(Colour::Wild, Value::Wild)
+                },
+                observation::PlayRecord::ColourDeclared {
+                    player: 0,
+                    colour: Colour::Green
+                },
+                observation::PlayRecord::CardDrawn { player: 1 },
+            ]
+        );
+    }
+
+    /// Registers an observer on `game` that collects its events.
+    fn record_events(game: &mut Game) -> Rc<RefCell<Vec<event::Event>>> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&events);
+        game.add_observer(Box::new(move |event: &event::Event| {
+            recorder.borrow_mut().push(*event)
+        }));
+        events
+    }
+
+    #[test]
+    fn test_events_of_draw_two_penalty() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Blue, Value::DrawTwo)], vec![]),
+            scripted_actor(1, vec![], vec![]),
+        ]);
+        let events = record_events(&mut game);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        let drawn = card(Colour::Blue, Value::Number(1));
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                event::Event::CardPlayed {
+                    player: 0,
+                    card: card(Colour::Blue, Value::DrawTwo),
+                },
+                event::Event::PenaltyApplied {
+                    player: 1,
+                    num_of_cards: 2,
+                },
+                event::Event::CardDrawn {
+                    player: 1,
+                    card: drawn,
+                },
+                event::Event::CardDrawn {
+                    player: 1,
+                    card: drawn,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_of_skip_reverse_and_colour() {
+        let hand = vec![
+            card(Colour::Blue, Value::Skip),
+            card(Colour::Blue, Value::Reverse),
+            card(Colour::Wild, Value::Wild),
+            card(Colour::Red, Value::Number(1)),
+        ];
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hand, vec![]),
+            scripted_actor(1, vec![], vec![]),
+            scripted_actor(2, vec![], vec![]),
+        ]);
+        let events = record_events(&mut game);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::EndTurn);
+        game.actor_index = 0;
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysWild(
+            0,
+            Colour::Green,
+        )));
+        game.run_game_phase(GameState::ChooseColour);
+
+        let events: Vec<event::Event> = events
+            .borrow()
+            .iter()
+            .filter(|event| !matches!(event, event::Event::CardPlayed { .. }))
+            .copied()
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                event::Event::PlayerSkipped { player: 1 },
+                event::Event::DirectionReversed {
+                    is_flow_clockwise: false,
+                },
+                event::Event::ColourChosen {
+                    player: 0,
+                    colour: Colour::Green,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_won_event_ends_the_game() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Blue, Value::Number(1))], vec![]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1))], vec![]),
+        ]);
+        let events = record_events(&mut game);
+        game.run_game_phase(GameState::ExecutePlayerAction(GameAction::PlayerPlaysCard(
+            0,
+        )));
+        game.run_game_phase(GameState::EndTurn);
+        game.run_game_phase(GameState::EndGame);
+        assert_eq!(
+            events.borrow().last(),
+            Some(&event::Event::RoundWon { player: 0 })
+        );
+    }
+
+    #[test]
+    fn test_suspended_game_stops_before_the_move() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Red, Value::Number(1)); 2], vec![])
+                .with_commands(vec![Some(actor::Command::Suspend)]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1)); 2], vec![]),
+        ]);
+        game.start_game();
+        assert!(game.is_suspended());
+        assert_eq!(game.get_state(), GameState::GetPlayerAction);
+        assert_eq!(hand_size(&game, 0), 2);
+
+        game.resume();
+        game.run_game_phase(game.get_state());
+        assert_eq!(
+            game.get_state(),
+            GameState::ExecutePlayerAction(GameAction::PlayerDraw)
+        );
+    }
+
+    #[test]
+    fn test_undo_rewinds_to_the_previous_turn() {
+        let mut game = create_test_game(vec![
+            scripted_actor(
+                0,
+                vec![card(Colour::Red, Value::Number(1)); 3],
+                vec![actor::UserAction::Play(0)],
+            )
+            .with_commands(vec![
+                None,
+                Some(actor::Command::Undo),
+                Some(actor::Command::Suspend),
+            ]),
+            scripted_actor(
+                1,
+                vec![card(Colour::Red, Value::Number(5))],
+                vec![actor::UserAction::Draw],
+            ),
+        ]);
+        game.enable_undo(0);
+        game.set_state(GameState::TurnStarts);
+        let observation = game.observe(0);
+
+        game.start_game();
+        assert!(game.is_suspended());
+        assert_eq!(game.get_state(), GameState::GetPlayerAction);
+        assert_eq!(game.observe(0), observation);
+        assert_eq!(hand_size(&game, 0), 3);
+        assert_eq!(hand_size(&game, 1), 1);
+        // Only the turns of the actor who can take them back are kept
+        let undo_turns: Vec<usize> = game.undo_history.iter().map(|(index, _)| *index).collect();
+        assert_eq!(undo_turns, vec![0]);
+    }
+
+    #[test]
+    fn test_saved_game_keeps_the_turns_to_take_back() {
+        let hands = [
+            vec![card(Colour::Red, Value::Number(1)); 3],
+            vec![card(Colour::Red, Value::Number(5))],
+        ];
+        let mut game = create_test_game(vec![
+            scripted_actor(0, hands[0].clone(), vec![actor::UserAction::Play(0)])
+                .with_commands(vec![None, Some(actor::Command::Suspend)]),
+            scripted_actor(1, hands[1].clone(), vec![actor::UserAction::Draw]),
+        ]);
+        game.enable_undo(0);
+        game.set_state(GameState::TurnStarts);
+        let observation = game.observe(0);
+        game.start_game();
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let actors = vec![
+            Box::new(scripted_actor(0, vec![], vec![]).with_commands(vec![
+                Some(actor::Command::Undo),
+                Some(actor::Command::Suspend),
+            ])) as GameActor,
+            Box::new(scripted_actor(1, vec![], vec![])) as GameActor,
+        ];
+        let mut restored = Game::from_snapshot(snapshot, actors).unwrap();
+        restored.enable_undo(0);
+        restored.resume();
+        restored.start_game();
+        assert_eq!(restored.observe(0), observation);
+        assert_eq!(hand_size(&restored, 0), 3);
+    }
+
+    #[test]
+    fn test_saved_turns_are_not_taken_back_without_undo() {
+        let mut game = create_test_game(vec![
+            scripted_actor(
+                0,
+                vec![card(Colour::Red, Value::Number(1)); 3],
+                vec![actor::UserAction::Play(0)],
+            )
+            .with_commands(vec![None, Some(actor::Command::Suspend)]),
+            scripted_actor(
+                1,
+                vec![card(Colour::Red, Value::Number(5))],
+                vec![actor::UserAction::Draw],
+            ),
+        ]);
+        game.enable_undo(0);
+        game.set_state(GameState::TurnStarts);
+        game.start_game();
+
+        let actors = vec![
+            Box::new(scripted_actor(0, vec![], vec![]).with_commands(vec![
+                Some(actor::Command::Undo),
+                Some(actor::Command::Suspend),
+            ])) as GameActor,
+            Box::new(scripted_actor(1, vec![], vec![])) as GameActor,
+        ];
+        let mut restored = Game::from_snapshot(game.snapshot(), actors).unwrap();
+        restored.resume();
+        restored.start_game();
+        assert!(restored.is_suspended());
+        assert_eq!(hand_size(&restored, 0), 2);
+    }
+
+    #[test]
+    fn test_failed_undo_stops_the_game() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Red, Value::Number(1)); 2], vec![])
+                .with_commands(vec![Some(actor::Command::Undo)]),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1)); 2], vec![]),
+        ]);
+        game.enable_undo(0);
+        let mut previous_turn = game.capture();
+        previous_turn.actor_states.pop();
+        game.undo_history = vec![(0, previous_turn), (0, game.capture())];
+        game.set_state(GameState::GetPlayerAction);
+
+        game.start_game();
+        assert!(game.is_suspended());
+        assert!(game.get_error().is_some());
+    }
+
+    #[test]
+    fn test_undo_without_a_previous_turn_asks_again() {
+        let mut game = create_test_game(vec![
+            scripted_actor(0, vec![card(Colour::Red, Value::Number(1)); 2], vec![]).with_commands(
+                vec![Some(actor::Command::Undo), Some(actor::Command::Suspend)],
+            ),
+            scripted_actor(1, vec![card(Colour::Red, Value::Number(1)); 2], vec![]),
+        ]);
+        game.enable_undo(0);
+        game.set_state(GameState::TurnStarts);
+
+        game.start_game();
+        assert!(game.is_suspended());
+        assert_eq!(game.get_state(), GameState::GetPlayerAction);
+        assert_eq!(hand_size(&game, 0), 2);
+    }
+
+    fn create_ai_game(seed: u64) -> Game {
+        let rules = rules::Rules::default();
+        let actors = [strategy::Difficulty::Easy, strategy::Difficulty::Normal]
+            .iter()
+            .enumerate()
+            .map(|(id, difficulty)| {
+                let budget = mcts::SearchBudget::Iterations(1);
+                create_ai_actor(id, *difficulty, &rules, budget, Some(seed))
+            })
+            .collect();
+        Game::new(actors, rules, Some(seed))
+    }
+
+    #[test]
+    fn test_restored_snapshot_plays_the_same_game() {
+        let mut game = create_ai_game(5);
+        assert!(!game.play_to_end(60));
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = Game::from_snapshot(snapshot, create_ai_game(9).into_actors()).unwrap();
+        assert_eq!(restored.observe(0), game.observe(0));
+
+        game.play_to_end(100_000);
+        restored.play_to_end(100_000);
+        assert_eq!(restored.get_winner(), game.get_winner());
+        assert_eq!(restored.observe(1), game.observe(1));
+    }
+
+    #[test]
+    fn test_snapshot_needs_the_same_number_of_actors() {
+        let snapshot = create_ai_game(0).snapshot();
+        let actors = vec![Box::new(scripted_actor(0, vec![], vec![])) as GameActor];
+        assert!(Game::from_snapshot(snapshot, actors).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_with_unknown_players_is_rejected() {
+        let game = create_ai_game(0);
+        let restore = |snapshot| Game::from_snapshot(snapshot, create_ai_game(1).into_actors());
+
+        let mut snapshot = game.snapshot();
+        snapshot.actor_index = 2;
+        assert!(restore(snapshot).is_err());
+
+        let mut snapshot = game.snapshot();
+        snapshot.dealer = 7;
+        assert!(restore(snapshot).is_err());
+
+        let mut snapshot = game.snapshot();
+        snapshot.actor_states.pop();
+        assert!(restore(snapshot).is_err());
+
+        let mut snapshot = game.snapshot();
+        snapshot.challenge = Some(WildDrawChallenge {
+            offender: 0,
+            victim: 3,
+            previous_colour: Colour::Red,
+            num_of_cards: 4,
+        });
+        assert!(restore(snapshot).is_err());
+
+        assert!(restore(game.snapshot()).is_ok());
+    }
+
+    #[test]
+    fn test_position_without_a_card_on_top_is_rejected() {
+        let empty_deck = || deck::Deck::from_piles(vec![], vec![], None, Some(0));
+        let actors = create_ai_game(0).into_actors();
+        let position = Game::from_position(actors, empty_deck(), rules::Rules::default(), 0, true);
+        assert!(position.is_err());
+
+        let mut snapshot = create_ai_game(0).snapshot();
+        snapshot.deck = empty_deck();
+        assert!(Game::from_snapshot(snapshot, create_ai_game(1).into_actors()).is_err());
+    }
+}
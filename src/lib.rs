@@ -0,0 +1,87 @@
+//! UNO game engine.
+//!
+//! The engine deals the cards, enforces the rules and asks an [`actor::Actor`] for the
+//! move of every player. Actors can be AI players ([`ai::AiActor`]), external programs
+//! ([`bot::BotActor`]) or any other implementation of the trait, so games can be played
+//! entirely without a terminal. Observers registered with `game::Game::add_observer`
+//! receive every [`event::Event`] of a game, e.g. for logging or statistics.
+//!
+//! # Examples
+//!
+//! Playing a game between AI players:
+//!
+//! ```
+//! use uno::flow::GameFlow;
+//! use uno::{game, mcts, rules, strategy};
+//!
+//! let rules = rules::Rules::default();
+//! let levels = [strategy::Difficulty::Easy, strategy::Difficulty::Normal];
+//! let actors = levels
+//!     .iter()
+//!     .enumerate()
+//!     .map(|(id, difficulty)| {
+//!         let budget = mcts::SearchBudget::Iterations(10);
+//!         game::create_ai_actor(id, *difficulty, &rules, budget, Some(7))
+//!     })
+//!     .collect();
+//!
+//! let mut game = game::Game::new(actors, rules, Some(7));
+//! assert!(game.play_to_end(100_000));
+//! let winner = game.get_winner().unwrap();
+//! assert!(winner < 2);
+//! ```
+//!
+//! Playing a match of several rounds up to a target score:
+//!
+//! ```
+//! use uno::{ai, game, rules, score, strategy};
+//!
+//! let actors: Vec<game::GameActor> = (0..3)
+//!     .map(|id| {
+//!         let actor = ai::AiActor::new(id, Box::new(strategy::NormalStrategy), Some(id as u64));
+//!         Box::new(actor) as game::GameActor
+//!     })
+//!     .collect();
+//!
+//! let mut uno_match = score::Match::new(actors, rules::Rules::default(), 200, Some(1));
+//! while uno_match.get_winner().is_none() {
+//!     uno_match.play_round().unwrap();
+//! }
+//! assert!(uno_match.get_scores().iter().any(|score| *score >= 200));
+//! ```
+//!
+//! Choosing every move of a player, here always the first legal action:
+//!
+//! ```
+//! use uno::{env, rules, strategy};
+//!
+//! let opponents = vec![strategy::Difficulty::Easy];
+//! let mut env = env::Env::new(opponents, rules::Rules::default(), 3).unwrap();
+//! let mut reward = 0.0;
+//! while !env.is_done() {
+//!     let action = env.action_mask().iter().position(|legal| *legal).unwrap();
+//!     reward = env.step(action).unwrap().1;
+//! }
+//! assert!([-1.0, 0.0, 1.0].contains(&reward));
+//! ```
+
+pub mod actor;
+pub mod ai;
+pub mod bot;
+pub mod card;
+pub mod counting;
+pub mod deck;
+mod default_deck;
+pub mod env;
+pub mod event;
+pub mod flow;
+pub mod game;
+pub mod mcts;
+pub mod observation;
+pub mod player;
+pub mod replay;
+pub mod rules;
+pub mod score;
+pub mod simulation;
+pub mod strategy;
+pub mod tournament;
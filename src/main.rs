@@ -1,21 +1,163 @@
 use cli::parse_input;
-use flow::GameFlow;
+use uno::{game, replay, rules, score, simulation, tournament};
 
-mod actor;
-mod ai;
-mod card;
 mod cli;
-mod deck;
-mod default_deck;
-mod flow;
-mod game;
-mod player;
+mod save;
 mod ui;
 
+/// Creates the actors of a game: a human in seat 0 followed by an AI player
+/// for each difficulty and an external bot for each command of `setup`.
+/// In verbose mode the AI players print the reason for every move, and in
+/// practice mode the human is offered to take turns back.
+/// The decisions of every actor are recorded by `recorder`.
+fn create_actors(
+    setup: &save::Setup,
+    rules: &rules::Rules,
+    seed: Option<u64>,
+    is_verbose: bool,
+    is_practice: bool,
+    recorder: &replay::Recorder,
+) -> Result<Vec<game::GameActor>, String> {
+    let mut actors: Vec<game::GameActor> =
+        vec![Box::new(ui::HumanActor::new(0, is_verbose, is_practice))];
+    actors.extend(setup.ai_levels.iter().enumerate().map(|(i, difficulty)| {
+        let mut actor = game::new_ai_actor(i + 1, *difficulty, rules, setup.search_budget, seed);
+        if is_verbose {
+            actor = actor.with_rationale(ui::print_ai_rationale);
+        }
+        Box::new(actor) as game::GameActor
+    }));
+    for command in &setup.bots {
+        actors.push(game::create_bot_actor(
+            actors.len(),
+            command,
+            setup.bot_timeout,
+            seed,
+        )?);
+    }
+    Ok(actors
+        .into_iter()
+        .map(|actor| recorder.record(actor))
+        .collect())
+}
+
 fn main() -> Result<(), String> {
     let args = parse_input();
-    game::check_game_attributes(args.num_of_players, args.num_of_cards)?;
-    let mut game = game::Game::new(args.num_of_players, args.num_of_cards);
-    game.start_game();
+    let rules = args.get_rules()?;
+    if let Some(cli::Command::Simulate(simulate_args)) = &args.command {
+        let simulation = simulation::Simulation {
+            ai_levels: simulate_args.ai_levels.clone(),
+            bots: args.bots.clone(),
+            bot_timeout: args.get_bot_timeout(),
+            rules,
+            search_budget: args.get_search_budget(),
+            num_of_games: simulate_args.games,
+            num_of_threads: simulate_args.threads,
+            seed: Some(args.seed.unwrap_or_else(rand::random)),
+        };
+        let report = simulation.run()?;
+        ui::print_simulation_report(&report);
+        if let Some(seed) = simulation.seed {
+            ui::announce_seed(seed);
+        }
+        return Ok(());
+    }
+    if let Some(cli::Command::Replay(replay_args)) = &args.command {
+        let replay = replay::Replay::load(&replay_args.file)?;
+        ui::view_replay(&replay, &replay.frames()?);
+        return Ok(());
+    }
+    if let Some(cli::Command::Tournament(tournament_args)) = &args.command {
+        if !args.bots.is_empty() {
+            return Err(String::from("Only AI strategies take part in a tournament"));
+        }
+        let tournament = tournament::Tournament {
+            strategies: tournament_args.strategies.clone(),
+            rules,
+            search_budget: args.get_search_budget(),
+            games_per_seating: tournament_args.games,
+            num_of_threads: tournament_args.threads,
+            // Every run plays the same deals unless a seed is given
+            seed: args.seed.unwrap_or_default(),
+        };
+        tournament.check()?;
+        ui::print_tournament_standings(&tournament.run()?);
+        return Ok(());
+    }
+    let replay_file = args.get_replay_file()?;
+    let (setup, recorder, mut uno_match) = match &args.load {
+        Some(path) => {
+            let saved = save::SavedMatch::load(path)?;
+            if args.practice && !saved.setup.is_practice {
+                return Err(String::from(
+                    "The match was not started in practice mode, resume it without --practice",
+                ));
+            }
+            let recorder = replay::Recorder::new(saved.replay);
+            let rules = saved.uno_match.get_rules();
+            let actors = create_actors(
+                &saved.setup,
+                rules,
+                None,
+                args.verbose,
+                saved.setup.is_practice,
+                &recorder,
+            )?;
+            let uno_match = score::Match::from_snapshot(saved.uno_match, actors)?;
+            (saved.setup, recorder, uno_match)
+        }
+        None => {
+            rules.check_game_attributes(args.num_of_players)?;
+            let setup = save::Setup {
+                ai_levels: args.get_ai_levels()?,
+                bots: args.bots.clone(),
+                bot_timeout: args.get_bot_timeout(),
+                search_budget: args.get_search_budget(),
+                is_practice: args.practice,
+            };
+            // The deals can only be replayed if they are shuffled with a known seed
+            let seed = Some(args.seed.unwrap_or_else(rand::random));
+            let recorder =
+                replay::Recorder::new(replay::Replay::new(rules.clone(), setup.player_names()));
+            let actors = create_actors(
+                &setup,
+                &rules,
+                seed,
+                args.verbose,
+                setup.is_practice,
+                &recorder,
+            )?;
+            let uno_match = score::Match::new(actors, rules, args.target_score, seed);
+            (setup, recorder, uno_match)
+        }
+    };
+    if setup.is_practice {
+        if !setup.bots.is_empty() {
+            return Err(String::from(
+                "Practice games are played against AI players only",
+            ));
+        }
+        uno_match.enable_undo(0); // The human player sits in seat 0
+    }
+    let result = ui::play_match(&mut uno_match, &recorder, replay_file.as_deref())?;
+    if let Some(path) = &replay_file {
+        ui::announce_replay_file(path);
+    }
+    if let Some(seed) = uno_match.get_seed() {
+        ui::announce_seed(seed);
+    }
+    match result {
+        Some(winner) => ui::announce_match_winner(winner),
+        None => {
+            let path = std::path::Path::new(save::DEFAULT_SAVE_FILE);
+            let saved = save::SavedMatch {
+                setup,
+                uno_match: uno_match.snapshot(),
+                replay: recorder.get_replay(),
+            };
+            saved.save(path)?;
+            ui::announce_saved_match(path);
+        }
+    }
     Ok(())
 }
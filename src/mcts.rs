@@ -0,0 +1,426 @@
+use crate::actor::Actor;
+use crate::default_deck::GAME_DECK;
+use crate::flow::{GameFlow, GameState};
+use crate::{actor, ai, card, deck, game, observation, rules, strategy};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+/// Default number of games simulated for every move.
+pub const DEFAULT_ITERATIONS: usize = 1000;
+
+/// Exploration constant of the UCB1 formula.
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// Number of game phases after which a playout is abandoned and counted as lost. It is
+/// far below `simulation::MAX_GAME_PHASES` because every move runs many playouts, and a
+/// playout that long says little about the move it started with.
+const MAX_PLAYOUT_PHASES: usize = 5000;
+
+/// How much searching the AI does before every move.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SearchBudget {
+    /// Simulates this many games.
+    Iterations(usize),
+    /// Simulates games until the time is up.
+    Time(Duration),
+}
+
+/// Information-set Monte Carlo Tree Search.
+///
+/// Every iteration deals the cards the AI has not seen to the opponents and the draw
+/// pile at random, then walks down a tree shared by all the deals: at every turn, the
+/// player to move picks a move with UCB1 among the moves legal in this deal. The first
+/// move that is not in the tree yet is added, and the game is played out with AI players.
+/// The result is credited to the player who made each move on the way down.
+/// The move of the AI that was explored the most is played.
+pub struct MctsStrategy {
+    rules: rules::Rules,
+    budget: SearchBudget,
+    fallback: strategy::NormalStrategy,
+}
+
+/// A move in the search tree. Cards are told apart by what they are rather than by
+/// their index, because the opponents' hands differ from one deal to the next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Move {
+    Draw,
+    Play(card::Card, Option<card::Colour>),
+}
+
+impl Move {
+    fn new(action: actor::UserAction, hand: &[card::Card]) -> Option<Move> {
+        match action {
+            actor::UserAction::Draw => Some(Move::Draw),
+            actor::UserAction::Play(i) => Some(Move::Play(*hand.get(i)?, None)),
+            actor::UserAction::PlayWild(i, colour) => Some(Move::Play(*hand.get(i)?, Some(colour))),
+            _ => None,
+        }
+    }
+}
+
+/// Search statistics of a move made by the player in `seat`.
+struct Edge {
+    seat: usize,
+    action: Move,
+    visits: usize,
+    wins: f64,
+    /// Number of visits of the parent in which the move was legal.
+    availability: usize,
+    /// Index of the node the move leads to.
+    child: usize,
+}
+
+impl Edge {
+    fn ucb(&self) -> f64 {
+        if self.visits == 0 {
+            return f64::INFINITY;
+        }
+        let visits = self.visits as f64;
+        self.wins / visits + EXPLORATION * ((self.availability as f64).ln() / visits).sqrt()
+    }
+}
+
+/// The moves tried from a position, whatever the deal.
+#[derive(Default)]
+struct Node {
+    edges: Vec<Edge>,
+}
+
+/// Search tree, the root is the position the AI has to move in.
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn new() -> Self {
+        Tree {
+            nodes: vec![Node::default()],
+        }
+    }
+
+    /// Picks one of `moves` for the player in `seat` at `node`. Returns the index of the
+    /// move in `moves`, the index of its edge, and whether the move was added to the tree.
+    fn select(
+        &mut self,
+        node: usize,
+        seat: usize,
+        moves: &[Move],
+        rng: &mut ChaCha8Rng,
+    ) -> (usize, usize, bool) {
+        let edges = &mut self.nodes[node].edges;
+        let mut untried = Vec::new();
+        for (index, action) in moves.iter().enumerate() {
+            match edges
+                .iter_mut()
+                .find(|edge| edge.seat == seat && edge.action == *action)
+            {
+                Some(edge) => edge.availability += 1,
+                None => untried.push(index),
+            }
+        }
+
+        if let Some(&index) = untried.choose(rng) {
+            let child = self.nodes.len();
+            let edges = &mut self.nodes[node].edges;
+            edges.push(Edge {
+                seat,
+                action: moves[index],
+                visits: 0,
+                wins: 0.0,
+                availability: 1,
+                child,
+            });
+            let edge = edges.len() - 1;
+            self.nodes.push(Node::default());
+            return (index, edge, true);
+        }
+
+        moves
+            .iter()
+            .enumerate()
+            .filter_map(|(index, action)| {
+                let edge = edges
+                    .iter()
+                    .position(|edge| edge.seat == seat && edge.action == *action)?;
+                Some((index, edge))
+            })
+            .max_by(|(_, a), (_, b)| edges[*a].ucb().total_cmp(&edges[*b].ucb()))
+            .map(|(index, edge)| (index, edge, false))
+            .unwrap() // Every move was tried, and there is always at least one
+    }
+}
+
+impl MctsStrategy {
+    pub fn new(rules: rules::Rules, budget: SearchBudget) -> Self {
+        MctsStrategy {
+            rules,
+            budget,
+            fallback: strategy::NormalStrategy,
+        }
+    }
+
+    fn has_budget(&self, iteration: usize, start: Instant) -> bool {
+        match self.budget {
+            SearchBudget::Iterations(iterations) => iteration < iterations,
+            SearchBudget::Time(time) => iteration == 0 || start.elapsed() < time,
+        }
+    }
+
+    fn search(
+        &self,
+        hand: &[card::Card],
+        observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> Tree {
+        let mut tree = Tree::new();
+        let start = Instant::now();
+        let mut iteration = 0;
+        while self.has_budget(iteration, start) {
+            iteration += 1;
+            let mut game = self.determinize(hand, observation, rng);
+            let path = descend(&mut tree, &mut game, rng);
+            if !matches!(game.get_state(), GameState::End) {
+                game.play_to_end(MAX_PLAYOUT_PHASES);
+            }
+            let winner = game.get_winner();
+            for (node, edge) in path {
+                let edge = &mut tree.nodes[node].edges[edge];
+                edge.visits += 1;
+                if winner == Some(edge.seat) {
+                    edge.wins += 1.0;
+                }
+            }
+        }
+        tree
+    }
+
+    /// Builds a game in the position the AI sees, dealing the cards it has not seen
+    /// at random. The AI sits in seat 0 and play goes clockwise in turn order.
+    fn determinize(
+        &self,
+        hand: &[card::Card],
+        observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> game::Game {
+        let mut unseen = GAME_DECK.to_vec();
+        for seen in hand.iter().chain(observation.discard_pile.iter()) {
+            if let Some(index) = unseen.iter().position(|card| card == seen) {
+                unseen.swap_remove(index);
+            }
+        }
+        unseen.shuffle(rng);
+
+        let mut hands = vec![hand.to_vec()];
+        for size in &observation.opponent_hand_sizes {
+            let size = (*size).min(unseen.len());
+            hands.push(unseen.split_off(unseen.len() - size));
+        }
+        let actors = hands
+            .into_iter()
+            .enumerate()
+            .map(|(seat, hand)| {
+                let mut actor =
+                    ai::AiActor::new(seat, Box::new(strategy::NormalStrategy), Some(rng.gen()));
+                hand.into_iter()
+                    .for_each(|card| actor.get_player_mut().take_card(card));
+                Box::new(actor) as game::GameActor
+            })
+            .collect();
+        let deck = deck::Deck::from_piles(
+            unseen,
+            observation.discard_pile.to_vec(),
+            observation.declared_colour,
+            Some(rng.gen()),
+        );
+        // The observed game has a card on top, so the position has one too
+        game::Game::from_position(actors, deck, self.rules.clone(), 0, true).unwrap()
+    }
+}
+
+impl strategy::Strategy for MctsStrategy {
+    fn choose_action(
+        &mut self,
+        hand: &[card::Card],
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> strategy::Choice {
+        // Only the moves of a regular turn are searched
+        if observation.pending_penalty > 0 || legal_actions.contains(&actor::UserAction::Pass) {
+            return self
+                .fallback
+                .choose_action(hand, legal_actions, observation, rng);
+        }
+
+        let moves: Vec<(Move, actor::UserAction)> = legal_actions
+            .iter()
+            .filter_map(|action| Some((Move::new(*action, hand)?, *action)))
+            .collect();
+        let (action, reason) = match moves.as_slice() {
+            [] => (None, Cow::Borrowed("no card can be played")),
+            [(_, action)] => (Some(*action), Cow::Borrowed("it is the only move")),
+            _ => {
+                let tree = self.search(hand, observation, rng);
+                let best = tree.nodes[0].edges.iter().max_by_key(|edge| edge.visits);
+                let action = best
+                    .and_then(|edge| moves.iter().find(|(action, _)| *action == edge.action))
+                    .map(|(_, action)| *action);
+                let reason = match best {
+                    Some(edge) => Cow::Owned(format!(
+                        "it won {:.0}% of the {} games simulated with it",
+                        100.0 * edge.wins / edge.visits.max(1) as f64,
+                        edge.visits
+                    )),
+                    None => Cow::Borrowed("no move was simulated"),
+                };
+                (action, reason)
+            }
+        };
+        strategy::Choice::new(
+            action.filter(|action| *action != actor::UserAction::Draw),
+            reason,
+        )
+    }
+
+    fn choose_colour(
+        &mut self,
+        hand: &[card::Card],
+        observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> card::Colour {
+        self.fallback.choose_colour(hand, observation, rng)
+    }
+}
+
+/// Plays `game` from the root of `tree`, choosing the moves of every regular turn in the
+/// tree, until a move is added to the tree or the game ends. Other decisions are left to
+/// the AI players. Returns the edges that were followed, as indexes of node and edge.
+fn descend(tree: &mut Tree, game: &mut game::Game, rng: &mut ChaCha8Rng) -> Vec<(usize, usize)> {
+    let mut path = Vec::new();
+    let mut node = 0;
+    for _ in 0..MAX_PLAYOUT_PHASES {
+        let state = game.get_state();
+        match state {
+            GameState::End => break,
+            GameState::GetPlayerAction => {}
+            _ => {
+                game.run_game_phase(state);
+                continue;
+            }
+        }
+
+        let seat = game.get_current_actor().get_id();
+        // The AI players declare UNO before choosing their move
+        let legal_actions = game.legal_actions(seat);
+        if legal_actions.contains(&actor::UserAction::Uno) {
+            game.declare_uno();
+        }
+        let player = game.get_actor(seat).get_player();
+        let mut moves = Vec::new();
+        let mut game_actions = Vec::new();
+        for action in legal_actions {
+            if let (Some(action), Ok(game_action)) = (
+                Move::new(action, player.get_hand()),
+                game.get_player_action(player, action),
+            ) {
+                moves.push(action);
+                game_actions.push(game_action);
+            }
+        }
+        if moves.is_empty() {
+            game.run_game_phase(state);
+            continue;
+        }
+
+        let (index, edge, is_new) = tree.select(node, seat, &moves, rng);
+        path.push((node, edge));
+        game.set_state(GameState::ExecutePlayerAction(game_actions[index]));
+        if is_new {
+            break;
+        }
+        node = tree.nodes[node].edges[edge].child;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card, Colour, Value};
+    use crate::strategy::Strategy;
+
+    fn card(colour: Colour, value: Value) -> Card {
+        Card { colour, value }
+    }
+
+    #[test]
+    fn test_determinize_deals_unseen_cards() {
+        let strategy = MctsStrategy::new(rules::Rules::default(), SearchBudget::Iterations(1));
+        let hand = vec![card(Colour::Red, Value::Number(1)); 2];
+        let top_card = card(Colour::Blue, Value::Number(3));
+        let observation = observation::create_test_observation(top_card, &[5, 7]);
+        let mut rng = deck::new_rng(Some(0));
+        let game = strategy.determinize(&hand, &observation, &mut rng);
+
+        let hand_sizes: Vec<usize> = (0..3)
+            .map(|seat| game.get_actor(seat).get_player().get_number_of_cards())
+            .collect();
+        assert_eq!(hand_sizes, vec![2, 5, 7]);
+        assert_eq!(game.get_actor(0).get_player().get_hand(), &hand);
+        let observation = game.observe(0);
+        assert_eq!(observation.draw_pile_size, 108 - 2 - 5 - 7 - 1);
+        assert_eq!(observation.top_card, card(Colour::Blue, Value::Number(3)));
+    }
+
+    #[test]
+    fn test_plays_the_winning_card() {
+        let mut strategy = MctsStrategy::new(rules::Rules::default(), SearchBudget::Iterations(50));
+        let hand = vec![card(Colour::Blue, Value::Number(2))];
+        let legal_actions = [actor::UserAction::Play(0), actor::UserAction::Draw];
+        let top_card = card(Colour::Blue, Value::Number(3));
+        let observation = observation::create_test_observation(top_card, &[1]);
+        let mut rng = deck::new_rng(Some(0));
+        let choice = strategy.choose_action(&hand, &legal_actions, &observation, &mut rng);
+        assert_eq!(choice.action, Some(actor::UserAction::Play(0)));
+        assert!(choice.reason.starts_with("it won 100% of the"));
+    }
+
+    #[test]
+    fn test_search_expands_the_opponents_moves() {
+        let strategy = MctsStrategy::new(rules::Rules::default(), SearchBudget::Iterations(200));
+        let hand = vec![
+            card(Colour::Blue, Value::Number(2)),
+            card(Colour::Blue, Value::Number(5)),
+            card(Colour::Red, Value::Number(3)),
+        ];
+        let top_card = card(Colour::Blue, Value::Number(3));
+        let observation = observation::create_test_observation(top_card, &[4]);
+        let mut rng = deck::new_rng(Some(0));
+        let tree = strategy.search(&hand, &observation, &mut rng);
+
+        let root = &tree.nodes[0];
+        assert!(root.edges.iter().all(|edge| edge.seat == 0));
+        assert_eq!(
+            root.edges.iter().map(|edge| edge.visits).sum::<usize>(),
+            200
+        );
+        let replies = &tree.nodes[root.edges[0].child].edges;
+        assert!(replies.iter().any(|edge| edge.seat == 1));
+        for edge in replies {
+            assert!(edge.visits <= root.edges[0].visits);
+            assert!(edge.availability >= edge.visits);
+        }
+    }
+
+    #[test]
+    fn test_time_budget_runs_at_least_one_iteration() {
+        let strategy =
+            MctsStrategy::new(rules::Rules::default(), SearchBudget::Time(Duration::ZERO));
+        assert!(strategy.has_budget(0, Instant::now()));
+        assert!(!strategy.has_budget(1, Instant::now() - Duration::from_millis(1)));
+    }
+}
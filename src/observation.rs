@@ -0,0 +1,82 @@
+use crate::card;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+/// Something that happened in the game that every player could see.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlayRecord {
+    /// The player with ID `player` played `card`.
+    CardPlayed { player: usize, card: card::Card },
+    /// The player with ID `player` drew a card.
+    CardDrawn { player: usize },
+    /// The player with ID `player` declared the colour in play after a wild card.
+    ColourDeclared { player: usize, colour: card::Colour },
+    /// The player with ID `player` has to draw `num_of_cards` as a penalty. The cards
+    /// drawn follow as `CardDrawn` records.
+    PenaltyApplied { player: usize, num_of_cards: usize },
+    /// The player with ID `player` swapped hands with the player with ID `other`.
+    HandsSwapped { player: usize, other: usize },
+    /// Every hand was passed to the next player in the direction of play.
+    HandsRotated,
+}
+
+/// What a player can see of the game when making a decision.
+///
+/// Other players' hands are hidden, only the number of cards they hold is known.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Observation {
+    /// Card on top of the discard pile.
+    pub top_card: card::Card,
+    /// Colour declared for the wild card on top of the discard pile, if any.
+    pub declared_colour: Option<card::Colour>,
+    /// IDs of the other players in turn order, starting with the next player.
+    pub opponent_ids: Vec<usize>,
+    /// Number of cards of the other players, in the same order as `opponent_ids`.
+    pub opponent_hand_sizes: Vec<usize>,
+    /// Cards in the discard pile, from the bottom to the top. Like the history, it is
+    /// shared with the game.
+    pub discard_pile: Rc<Vec<card::Card>>,
+    /// Number of cards left in the draw pile.
+    pub draw_pile_size: usize,
+    /// Number of cards the player has to draw unless they stack a draw card.
+    pub pending_penalty: usize,
+    /// Tells whether play goes clockwise, i.e. to players with increasing IDs.
+    pub is_flow_clockwise: bool,
+    /// Everything played so far in the game, oldest first. It is shared with the game
+    /// rather than copied for every decision.
+    pub history: Rc<Vec<PlayRecord>>,
+}
+
+impl Observation {
+    /// Returns the number of cards of the next player.
+    pub fn next_hand_size(&self) -> Option<usize> {
+        self.opponent_hand_sizes.first().copied()
+    }
+
+    /// Tells whether the next player has no more cards than any other opponent. There
+    /// is no leader without opponents.
+    pub fn next_is_leader(&self) -> bool {
+        self.next_hand_size()
+            .is_some_and(|next| self.opponent_hand_sizes.iter().all(|size| next <= *size))
+    }
+}
+
+/// Creates what a player sees at the start of a game with opponents holding
+/// `opponent_hand_sizes` cards in turn order, and `top_card` alone on the discard pile.
+#[cfg(test)]
+pub(crate) fn create_test_observation(
+    top_card: card::Card,
+    opponent_hand_sizes: &[usize],
+) -> Observation {
+    Observation {
+        top_card,
+        declared_colour: None,
+        opponent_ids: (1..=opponent_hand_sizes.len()).collect(),
+        opponent_hand_sizes: opponent_hand_sizes.to_vec(),
+        discard_pile: Rc::new(vec![top_card]),
+        draw_pile_size: 50,
+        pending_penalty: 0,
+        is_flow_clockwise: true,
+        history: Rc::default(),
+    }
+}
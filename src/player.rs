@@ -1,4 +1,5 @@
 use crate::card::Card;
+use serde::{Deserialize, Serialize};
 
 /// Type alias for results returned by player-related operations.
 ///
@@ -19,6 +20,7 @@ pub enum Error {
 }
 
 /// Represents a player in the game, holding a hand of cards.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Player {
     /// A vector of `Card` structs representing the player's hand.
     hand: Vec<Card>,
@@ -68,20 +70,10 @@ impl Player {
         &self.hand
     }
 
-    /// Converts the player's hand to a string representation.
-    /// Returns a string with each card and its index.
-    fn hand_to_string(&self) -> String {
-        self.hand
-            .iter()
-            .enumerate()
-            .map(|(i, card)| format!("{i:02}: {card}"))
-            .collect::<Vec<_>>()
-            .join("\n")
-    }
-
-    /// Prints the player's hand to the console.
-    pub fn print_hand(&self) {
-        println!("{}", self.hand_to_string());
+    /// Replaces the player's hand with the given cards.
+    /// Returns the cards that were previously in the hand.
+    pub fn replace_hand(&mut self, hand: Vec<Card>) -> Vec<Card> {
+        std::mem::replace(&mut self.hand, hand)
     }
 
     /// Creates a new player with an empty hand.
@@ -163,6 +155,19 @@ mod tests {
         assert_eq!(player.get_hand(), &vec![card]);
     }
 
+    #[test]
+    fn test_replace_hand() {
+        let card = card::Card {
+            colour: card::Colour::Red,
+            value: card::Value::Number(1),
+        };
+        let mut player = Player::new();
+        player.hand.push(card);
+        let previous_hand = player.replace_hand(Vec::new());
+        assert_eq!(previous_hand, vec![card]);
+        assert!(player.is_hand_empty());
+    }
+
     #[test]
     fn test_get_card_ok() {
         let card = card::Card {
@@ -181,18 +186,4 @@ mod tests {
         let result = player.get_card(0);
         assert_eq!(result, Err(Error::IndexOutOfBounds));
     }
-
-    #[test]
-    fn test_print_hand() {
-        let card = card::Card {
-            colour: card::Colour::Red,
-            value: card::Value::Number(1),
-        };
-        let mut player = Player::new();
-        player.hand.push(card);
-        player.hand.push(card);
-        player.hand.push(card);
-        let expected = format!("00: {card}\n01: {card}\n02: {card}", card = card);
-        assert_eq!(player.hand_to_string(), expected);
-    }
 }
@@ -0,0 +1,555 @@
+use crate::flow::{GameFlow, GameState};
+use crate::{actor, card, event, game, observation, player, rules, simulation};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::rc::Rc;
+
+/// A decision an actor made when the game asked for it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Decision {
+    /// A move on the actor's turn, with a card just drawn or against a pending penalty.
+    Action(actor::UserAction),
+    Colour(card::Colour),
+    Challenge(bool),
+    CatchMissedUno(bool),
+    JumpIn(bool),
+    SwapTarget(usize),
+    /// The actor took their last turn back instead of moving.
+    Undo,
+}
+
+/// A recorded round. The deal follows from the seed and the dealer, and everything
+/// else from the decisions of the actors, listed with their IDs in the order they
+/// were made.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Round {
+    pub seed: u64,
+    pub dealer: usize,
+    /// Seats that could take their turns back.
+    #[serde(default)]
+    pub undo_seats: Vec<usize>,
+    pub decisions: Vec<(usize, Decision)>,
+}
+
+/// A recorded match, which can be played back without the actors that played it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub rules: rules::Rules,
+    /// Name of the player in every seat.
+    pub players: Vec<String>,
+    pub rounds: Vec<Round>,
+}
+
+/// Position at the start of a turn of a replayed round, with everything that happened
+/// during the turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Index of the round in the replay.
+    pub round: usize,
+    /// Number of the turn in the round, starting at 0.
+    pub turn: usize,
+    /// ID of the player whose turn it is.
+    pub player: usize,
+    /// Hand of every player, indexed by ID.
+    pub hands: Vec<Vec<card::Card>>,
+    pub top_card: card::Card,
+    pub colour_in_play: Option<card::Colour>,
+    pub is_flow_clockwise: bool,
+    pub draw_pile_size: usize,
+    pub events: Vec<event::Event>,
+}
+
+impl Replay {
+    pub fn new(rules: rules::Rules, players: Vec<String>) -> Self {
+        Replay {
+            rules,
+            players,
+            rounds: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Cannot write the replay file {}: {e}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read the replay file {}: {e}", path.display()))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid replay file {}: {e}", path.display()))
+    }
+
+    /// Plays the recorded rounds back and returns the position at the start of every
+    /// turn, followed by the final position of each round.
+    /// Fails if a round does not end the way it was recorded.
+    pub fn frames(&self) -> Result<Vec<Frame>, String> {
+        let mut frames = Vec::new();
+        for (index, round) in self.rounds.iter().enumerate() {
+            frames.extend(self.round_frames(index, round)?);
+        }
+        Ok(frames)
+    }
+
+    fn round_frames(&self, index: usize, round: &Round) -> Result<Vec<Frame>, String> {
+        let actors = (0..self.players.len())
+            .map(|id| Box::new(ReplayActor::new(id, round)) as game::GameActor)
+            .collect();
+        let mut game = game::Game::new(actors, self.rules.clone(), Some(round.seed));
+        game.set_dealer(round.dealer);
+        // Recorded undos rewind the replay as they rewound the game
+        for seat in &round.undo_seats {
+            game.enable_undo(*seat);
+        }
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&events);
+        game.add_observer(Box::new(move |event: &event::Event| {
+            recorder.borrow_mut().push(*event)
+        }));
+
+        let mut frames: Vec<Frame> = Vec::new();
+        for _ in 0..simulation::MAX_GAME_PHASES {
+            let state = game.get_state();
+            // A round recorded until the match was suspended or quit ends early
+            let is_over = matches!(state, GameState::End) || game.is_suspended();
+            if is_over || matches!(state, GameState::TurnStarts) {
+                let turn_events = std::mem::take(&mut *events.borrow_mut());
+                // The events of the deal come before the first turn and are left out
+                if let Some(frame) = frames.last_mut() {
+                    frame.events = turn_events;
+                }
+                frames.push(capture_frame(&game, index, frames.len()));
+            }
+            if is_over {
+                return Ok(frames);
+            }
+            game.run_game_phase(state);
+        }
+        Err(format!("Round {} of the replay does not end", index + 1))
+    }
+}
+
+fn capture_frame(game: &game::Game, round: usize, turn: usize) -> Frame {
+    Frame {
+        round,
+        turn,
+        player: game.get_current_actor().get_id(),
+        hands: (0..game.get_number_of_actors())
+            .map(|index| game.get_actor(index).get_player().get_hand().clone())
+            .collect(),
+        top_card: game.get_top_card(),
+        colour_in_play: game.get_colour_in_play(),
+        is_flow_clockwise: game.is_flow_clockwise(),
+        draw_pile_size: game.get_draw_pile_size(),
+        events: Vec::new(),
+    }
+}
+
+/// Records the decisions of the actors of a match into a replay.
+/// Clones share the same replay.
+#[derive(Clone)]
+pub struct Recorder {
+    replay: Rc<RefCell<Replay>>,
+}
+
+impl Recorder {
+    /// Creates a recorder adding rounds to `replay`. The decisions recorded before a
+    /// round is started are added to the last round of `replay`, e.g. to continue a
+    /// suspended match.
+    pub fn new(replay: Replay) -> Self {
+        Recorder {
+            replay: Rc::new(RefCell::new(replay)),
+        }
+    }
+
+    /// Starts recording a new round dealt with `seed` by the player with ID `dealer`, in
+    /// which the players in `undo_seats` can take their turns back.
+    pub fn start_round(&self, seed: u64, dealer: usize, undo_seats: &[usize]) {
+        self.replay.borrow_mut().rounds.push(Round {
+            seed,
+            dealer,
+            undo_seats: undo_seats.to_vec(),
+            decisions: Vec::new(),
+        });
+    }
+
+    /// Wraps `actor` so that its decisions are recorded.
+    pub fn record(&self, actor: game::GameActor) -> game::GameActor {
+        Box::new(RecordingActor {
+            actor,
+            recorder: self.clone(),
+            command: None,
+        })
+    }
+
+    pub fn get_replay(&self) -> Replay {
+        self.replay.borrow().clone()
+    }
+
+    fn push(&self, id: usize, decision: Decision) {
+        if let Some(round) = self.replay.borrow_mut().rounds.last_mut() {
+            round.decisions.push((id, decision));
+        }
+    }
+}
+
+/// Actor passing every decision of the actor it wraps to a recorder.
+struct RecordingActor {
+    actor: game::GameActor,
+    recorder: Recorder,
+    /// Command the actor gave instead of its last move.
+    command: Option<actor::Command>,
+}
+
+impl RecordingActor {
+    fn record<T>(&self, decision: T, to_decision: impl Fn(T) -> Decision) -> T
+    where
+        T: Copy,
+    {
+        self.recorder
+            .push(self.actor.get_id(), to_decision(decision));
+        decision
+    }
+
+    fn record_action(&mut self, action: actor::UserAction) -> actor::UserAction {
+        self.command = self.actor.take_command();
+        match self.command {
+            // Suspending the game is not a decision, the actor is asked again on resuming
+            Some(actor::Command::Suspend) => action,
+            Some(actor::Command::Undo) => {
+                self.recorder.push(self.actor.get_id(), Decision::Undo);
+                action
+            }
+            None => self.record(action, Decision::Action),
+        }
+    }
+}
+
+impl actor::Actor for RecordingActor {
+    fn get_id(&self) -> usize {
+        self.actor.get_id()
+    }
+
+    fn get_player(&self) -> &player::Player {
+        self.actor.get_player()
+    }
+
+    fn get_player_mut(&mut self) -> &mut player::Player {
+        self.actor.get_player_mut()
+    }
+
+    fn get_turn_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        let action = self.actor.get_turn_action(legal_actions, observation);
+        self.record_action(action)
+    }
+
+    fn get_drawn_card_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        let action = self.actor.get_drawn_card_action(legal_actions, observation);
+        self.record_action(action)
+    }
+
+    fn get_stack_action(
+        &mut self,
+        penalty: usize,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        let action = self
+            .actor
+            .get_stack_action(penalty, legal_actions, observation);
+        self.record_action(action)
+    }
+
+    fn decide_challenge(&mut self) -> bool {
+        let is_challenged = self.actor.decide_challenge();
+        self.record(is_challenged, Decision::Challenge)
+    }
+
+    fn catch_missed_uno(&mut self, offender_id: usize) -> bool {
+        let is_caught = self.actor.catch_missed_uno(offender_id);
+        self.record(is_caught, Decision::CatchMissedUno)
+    }
+
+    fn decide_jump_in(&mut self, card_index: usize) -> bool {
+        let jumps_in = self.actor.decide_jump_in(card_index);
+        self.record(jumps_in, Decision::JumpIn)
+    }
+
+    fn get_swap_target(&mut self, hand_sizes: &[usize]) -> usize {
+        let target = self.actor.get_swap_target(hand_sizes);
+        self.record(target, Decision::SwapTarget)
+    }
+
+    fn get_color_choice(&mut self, observation: &observation::Observation) -> card::Colour {
+        let colour = self.actor.get_color_choice(observation);
+        self.record(colour, Decision::Colour)
+    }
+
+    fn pre_turn_action(&mut self) {
+        self.actor.pre_turn_action();
+    }
+
+    fn post_turn_action(&mut self) {
+        self.actor.post_turn_action();
+    }
+
+    fn take_command(&mut self) -> Option<actor::Command> {
+        self.command.take()
+    }
+
+    fn save_state(&self) -> serde_json::Value {
+        self.actor.save_state()
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) -> Result<(), String> {
+        self.actor.load_state(state)
+    }
+}
+
+/// Actor repeating the decisions recorded for its seat in a round.
+struct ReplayActor {
+    id: usize,
+    player: player::Player,
+    decisions: VecDeque<Decision>,
+    /// Command repeated instead of the last decision.
+    command: Option<actor::Command>,
+}
+
+impl ReplayActor {
+    fn new(id: usize, round: &Round) -> Self {
+        ReplayActor {
+            id,
+            player: player::Player::new(),
+            decisions: round
+                .decisions
+                .iter()
+                .filter(|(actor_id, _)| *actor_id == id)
+                .map(|(_, decision)| *decision)
+                .collect(),
+            command: None,
+        }
+    }
+
+    /// Returns the next recorded decision if it answers what the game asks, as picked
+    /// by `answer`. Otherwise repeats the recorded undo, or suspends the game where the
+    /// recording stops, and returns `default`, which the game ignores.
+    fn next_decision<T>(&mut self, default: T, answer: impl FnOnce(Decision) -> Option<T>) -> T {
+        let decision = self.decisions.pop_front();
+        if let Some(answer) = decision.and_then(answer) {
+            return answer;
+        }
+        self.command = match decision {
+            Some(Decision::Undo) => Some(actor::Command::Undo),
+            _ => Some(actor::Command::Suspend),
+        };
+        default
+    }
+
+    fn next_action(&mut self) -> actor::UserAction {
+        self.next_decision(actor::UserAction::Draw, |decision| match decision {
+            Decision::Action(action) => Some(action),
+            _ => None,
+        })
+    }
+}
+
+impl actor::Actor for ReplayActor {
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_player(&self) -> &player::Player {
+        &self.player
+    }
+
+    fn get_player_mut(&mut self) -> &mut player::Player {
+        &mut self.player
+    }
+
+    fn get_turn_action(
+        &mut self,
+        _legal_actions: &[actor::UserAction],
+        _observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.next_action()
+    }
+
+    fn get_drawn_card_action(
+        &mut self,
+        _legal_actions: &[actor::UserAction],
+        _observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.next_action()
+    }
+
+    fn get_stack_action(
+        &mut self,
+        _penalty: usize,
+        _legal_actions: &[actor::UserAction],
+        _observation: &observation::Observation,
+    ) -> actor::UserAction {
+        self.next_action()
+    }
+
+    fn decide_challenge(&mut self) -> bool {
+        self.next_decision(false, |decision| match decision {
+            Decision::Challenge(is_challenged) => Some(is_challenged),
+            _ => None,
+        })
+    }
+
+    fn catch_missed_uno(&mut self, _offender_id: usize) -> bool {
+        self.next_decision(false, |decision| match decision {
+            Decision::CatchMissedUno(is_caught) => Some(is_caught),
+            _ => None,
+        })
+    }
+
+    fn decide_jump_in(&mut self, _card_index: usize) -> bool {
+        self.next_decision(false, |decision| match decision {
+            Decision::JumpIn(jumps_in) => Some(jumps_in),
+            _ => None,
+        })
+    }
+
+    fn get_swap_target(&mut self, _hand_sizes: &[usize]) -> usize {
+        self.next_decision(self.id, |decision| match decision {
+            Decision::SwapTarget(target) => Some(target),
+            _ => None,
+        })
+    }
+
+    fn get_color_choice(&mut self, _observation: &observation::Observation) -> card::Colour {
+        self.next_decision(card::Colour::Red, |decision| match decision {
+            Decision::Colour(colour) => Some(colour),
+            _ => None,
+        })
+    }
+
+    fn pre_turn_action(&mut self) {
+        // Do nothing
+    }
+
+    fn post_turn_action(&mut self) {
+        // Do nothing
+    }
+
+    fn take_command(&mut self) -> Option<actor::Command> {
+        self.command.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mcts, strategy};
+
+    const SEED: u64 = 4;
+
+    /// Sets up a recorded game between AI players.
+    fn start_recorded_game() -> (Recorder, game::Game) {
+        let rules = rules::Rules::default();
+        let levels = [strategy::Difficulty::Easy, strategy::Difficulty::Normal];
+        let players = levels.iter().map(|level| level.to_string()).collect();
+        let recorder = Recorder::new(Replay::new(rules.clone(), players));
+        recorder.start_round(SEED, 1, &[]);
+        let actors = levels
+            .iter()
+            .enumerate()
+            .map(|(id, difficulty)| {
+                let budget = mcts::SearchBudget::Iterations(1);
+                recorder.record(game::create_ai_actor(
+                    id,
+                    *difficulty,
+                    &rules,
+                    budget,
+                    Some(SEED),
+                ))
+            })
+            .collect();
+        let mut game = game::Game::new(actors, rules, Some(SEED));
+        game.set_dealer(1);
+        (recorder, game)
+    }
+
+    /// Records a game between AI players and returns the replay with the finished game.
+    fn record_game() -> (Replay, game::Game) {
+        let (recorder, mut game) = start_recorded_game();
+        assert!(game.play_to_end(simulation::MAX_GAME_PHASES));
+        (recorder.get_replay(), game)
+    }
+
+    #[test]
+    fn test_replay_reaches_the_recorded_end() {
+        let (replay, game) = record_game();
+        let frames = replay.frames().unwrap();
+        let last_frame = frames.last().unwrap();
+        for (id, hand) in last_frame.hands.iter().enumerate() {
+            assert_eq!(hand, game.get_actor(id).get_player().get_hand());
+        }
+        let winner = game.get_winner().unwrap();
+        let last_turn = &frames[frames.len() - 2];
+        assert_eq!(
+            last_turn.events.last(),
+            Some(&event::Event::RoundWon { player: winner })
+        );
+    }
+
+    #[test]
+    fn test_frames_start_every_turn() {
+        let (replay, game) = record_game();
+        let frames = replay.frames().unwrap();
+        // The winning turn does not count as a passed turn, and the final position follows it
+        assert_eq!(frames.len(), game.get_number_of_turns() + 2);
+        assert_eq!(frames[0].turn, 0);
+        assert!(frames.iter().all(|frame| frame.hands.len() == 2));
+    }
+
+    #[test]
+    fn test_ignored_undo_is_not_replayed() {
+        let (replay, _) = record_game();
+        let frames = replay.frames().unwrap();
+        // Seat 0 asks to take a turn back in a game that does not let them
+        let mut ignored_undo = replay.clone();
+        let decisions = &mut ignored_undo.rounds[0].decisions;
+        decisions.insert(6, (0, Decision::Undo));
+        assert_eq!(ignored_undo.frames().unwrap(), frames);
+    }
+
+    #[test]
+    fn test_unfinished_round_stops_where_the_recording_stops() {
+        // The recording stops before a move, then before a challenge
+        for num_of_decisions in [4, 17] {
+            let (mut replay, _) = record_game();
+            replay.rounds[0].decisions.truncate(num_of_decisions);
+
+            // Plays the recorded game again up to the phase making the next decision
+            let (recorder, mut game) = start_recorded_game();
+            let mut position = capture_frame(&game, 0, 0);
+            while recorder.get_replay().rounds[0].decisions.len() <= num_of_decisions {
+                position = capture_frame(&game, 0, 0);
+                game.run_game_phase(game.get_state());
+            }
+
+            let frames = replay.frames().unwrap();
+            let last_frame = frames.last().unwrap();
+            let expected = Frame {
+                turn: last_frame.turn,
+                events: last_frame.events.clone(),
+                ..position
+            };
+            assert_eq!(*last_frame, expected);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::default_deck::GAME_DECK;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// House rules that change how a game is played.
+///
+/// The defaults follow the official rules. A rules file only needs to list the
+/// rules that differ from the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Draw Two and Wild Draw cards can be stacked to pass the penalty on.
+    pub stacking: bool,
+    /// A player holding a card identical to the one just played may play it out of turn.
+    pub jump_in: bool,
+    /// Playing a 7 swaps hands with another player, playing a 0 passes every hand along.
+    pub seven_o: bool,
+    /// A player who draws keeps drawing until they get a playable card.
+    pub draw_until_playable: bool,
+    /// A player who can play a card is not allowed to draw instead.
+    pub forced_play: bool,
+    /// In a two-player game, a Reverse acts like a Skip, as in the official rules.
+    pub two_player_reverse_skips: bool,
+    /// Maximum number of players in a game.
+    pub max_players: usize,
+    /// Maximum number of cards dealt to each player.
+    pub max_cards: usize,
+    /// Number of cards dealt to each player at the start of the game.
+    pub starting_hand_size: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            stacking: false,
+            jump_in: false,
+            seven_o: false,
+            draw_until_playable: false,
+            forced_play: false,
+            two_player_reverse_skips: true,
+            max_players: 10,
+            max_cards: 10,
+            starting_hand_size: 7,
+        }
+    }
+}
+
+impl Rules {
+    /// Parses rules from the contents of a TOML file.
+    pub fn from_toml(contents: &str) -> Result<Rules, String> {
+        toml::from_str(contents).map_err(|e| format!("Invalid rules: {e}"))
+    }
+
+    /// Loads rules from a TOML file.
+    pub fn from_file(path: &Path) -> Result<Rules, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read rules file {}: {e}", path.display()))?;
+        Rules::from_toml(&contents)
+    }
+
+    /// Checks that a game with the given number of players can be played with these rules,
+    /// including that the deck holds every starting hand plus the starting card.
+    pub fn check_game_attributes(&self, num_of_players: usize) -> Result<(), String> {
+        if num_of_players < 2 {
+            return Err(String::from("A game needs at least 2 players"));
+        }
+
+        if self.starting_hand_size > self.max_cards {
+            return Err(format!("The maximum number of cards is {}", self.max_cards));
+        }
+
+        if num_of_players > self.max_players {
+            return Err(format!(
+                "The maximum number of players is {}",
+                self.max_players
+            ));
+        }
+
+        if num_of_players * self.starting_hand_size + 1 > GAME_DECK.len() {
+            return Err(format!(
+                "The deck of {} cards cannot deal {} cards to {num_of_players} players",
+                GAME_DECK.len(),
+                self.starting_hand_size
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml_empty_uses_defaults() {
+        assert_eq!(Rules::from_toml(""), Ok(Rules::default()));
+    }
+
+    #[test]
+    fn test_from_toml_overrides_listed_rules() {
+        let rules = Rules::from_toml("stacking = true\nstarting_hand_size = 5\n").unwrap();
+        assert!(rules.stacking);
+        assert_eq!(rules.starting_hand_size, 5);
+        assert!(!rules.jump_in);
+        assert!(rules.two_player_reverse_skips);
+        assert_eq!(rules.max_players, 10);
+    }
+
+    #[test]
+    fn test_from_toml_unknown_rule() {
+        assert!(Rules::from_toml("no_such_rule = true").is_err());
+    }
+
+    #[test]
+    fn test_from_toml_wrong_type() {
+        assert!(Rules::from_toml("stacking = 3").is_err());
+    }
+
+    #[test]
+    fn test_check_game_attributes_ok() {
+        assert_eq!(Rules::default().check_game_attributes(10), Ok(()));
+    }
+
+    #[test]
+    fn test_check_game_attributes_too_many_players() {
+        assert!(Rules::default().check_game_attributes(11).is_err());
+    }
+
+    #[test]
+    fn test_check_game_attributes_too_many_cards() {
+        let rules = Rules {
+            starting_hand_size: 11,
+            ..Rules::default()
+        };
+        assert!(rules.check_game_attributes(2).is_err());
+    }
+
+    #[test]
+    fn test_check_game_attributes_too_few_players() {
+        assert!(Rules::default().check_game_attributes(1).is_err());
+        assert!(Rules::default().check_game_attributes(0).is_err());
+    }
+
+    #[test]
+    fn test_check_game_attributes_deck_too_small() {
+        let rules = Rules {
+            max_players: 20,
+            max_cards: 20,
+            starting_hand_size: 20,
+            ..Rules::default()
+        };
+        assert!(rules.check_game_attributes(5).is_ok());
+        assert!(rules.check_game_attributes(6).is_err());
+    }
+}
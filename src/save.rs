@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use uno::{mcts, replay, score, strategy};
+
+/// File the match is saved to when the human player enters `save`.
+pub const DEFAULT_SAVE_FILE: &str = "uno-save.json";
+
+/// Players seated at the table, used to seat them again when a match is resumed.
+/// The human player always sits in seat 0.
+#[derive(Serialize, Deserialize)]
+pub struct Setup {
+    pub ai_levels: Vec<strategy::Difficulty>,
+    pub bots: Vec<String>,
+    pub bot_timeout: Duration,
+    pub search_budget: mcts::SearchBudget,
+    /// Whether the human player can take turns back, for the whole match.
+    #[serde(default)]
+    pub is_practice: bool,
+}
+
+impl Setup {
+    /// Returns the name of the player in every seat.
+    pub fn player_names(&self) -> Vec<String> {
+        let ai_names = self
+            .ai_levels
+            .iter()
+            .map(|difficulty| difficulty.to_string());
+        let bot_names = self.bots.iter().map(|command| format!("bot {command}"));
+        std::iter::once(String::from("human"))
+            .chain(ai_names)
+            .chain(bot_names)
+            .collect()
+    }
+}
+
+/// A suspended match, saved as JSON.
+#[derive(Serialize, Deserialize)]
+pub struct SavedMatch {
+    pub setup: Setup,
+    pub uno_match: score::MatchSnapshot,
+    /// Recording of the match so far, continued when the match is resumed.
+    pub replay: replay::Replay,
+}
+
+impl SavedMatch {
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Cannot write the save file {}: {e}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<SavedMatch, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read the save file {}: {e}", path.display()))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid save file {}: {e}", path.display()))
+    }
+}
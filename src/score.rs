@@ -0,0 +1,366 @@
+use crate::card::{Card, Value};
+use crate::flow::GameFlow;
+use crate::game;
+use crate::rules;
+use serde::{Deserialize, Serialize};
+
+/// Score a player must reach to win a match, as in the official rules.
+pub const DEFAULT_TARGET_SCORE: usize = 500;
+
+/// Returns the points a card is worth when left in an opponent's hand.
+pub fn card_points(card: &Card) -> usize {
+    match card.value {
+        Value::Number(n) => n,
+        Value::Skip | Value::Reverse | Value::DrawTwo => 20,
+        Value::Wild | Value::WildDraw(_) => 50,
+    }
+}
+
+/// Returns the points a hand of cards is worth.
+pub fn hand_points(hand: &[Card]) -> usize {
+    hand.iter().map(card_points).sum()
+}
+
+/// A match of several rounds, played until a player reaches the target score.
+///
+/// The winner of each round scores the points of the cards left in the other
+/// players' hands, and the dealer rotates after every round.
+pub struct Match {
+    actors: Vec<game::GameActor>,
+    /// Round suspended before it ended, which holds the actors until it is resumed.
+    game: Option<game::Game>,
+    scores: Vec<usize>,
+    dealer: usize,
+    round: u64,
+    target_score: usize,
+    rules: rules::Rules,
+    seed: Option<u64>,
+    /// Indexes of the actors who can take their turns back.
+    undo_actors: Vec<usize>,
+}
+
+/// Everything needed to resume a match, e.g. from a save file.
+#[derive(Serialize, Deserialize)]
+pub struct MatchSnapshot {
+    scores: Vec<usize>,
+    dealer: usize,
+    round: u64,
+    target_score: usize,
+    rules: rules::Rules,
+    seed: Option<u64>,
+    /// Round in progress, if the match was suspended during a round.
+    game: Option<game::Snapshot>,
+    /// Internal state of the actors, if no round is in progress.
+    actor_states: Vec<serde_json::Value>,
+}
+
+impl MatchSnapshot {
+    pub fn get_rules(&self) -> &rules::Rules {
+        &self.rules
+    }
+}
+
+impl Match {
+    /// Plays the next round, or resumes the suspended one, and scores it.
+    /// Returns the ID of the player who wins the round, if anyone does.
+    /// Returns `None` without scoring if an actor suspends the round again.
+    /// Fails if the round stops on an error, after which the match cannot go on.
+    pub fn play_round(&mut self) -> Result<Option<usize>, String> {
+        let mut game = match self.game.take() {
+            Some(mut game) => {
+                game.resume();
+                game
+            }
+            None => {
+                let actors = std::mem::take(&mut self.actors);
+                let seed = self.get_round_seed();
+                let mut game = game::Game::new(actors, self.rules.clone(), seed);
+                game.set_dealer(self.dealer);
+                for actor_index in &self.undo_actors {
+                    game.enable_undo(*actor_index);
+                }
+                game
+            }
+        };
+        game.start_game();
+        if let Some(error) = game.get_error() {
+            return Err(error.to_string());
+        }
+        if game.is_suspended() {
+            self.game = Some(game);
+            return Ok(None);
+        }
+
+        let winner = game.get_winner();
+        self.actors = game.into_actors();
+        if let Some(winner) = winner {
+            self.record_round(winner);
+        }
+        for actor in self.actors.iter_mut() {
+            actor.get_player_mut().replace_hand(Vec::new());
+        }
+        self.dealer = (self.dealer + 1) % self.actors.len();
+        self.round += 1;
+        Ok(winner)
+    }
+
+    /// Awards the winner of the round the points left in every player's hand.
+    fn record_round(&mut self, winner: usize) {
+        let points: usize = self
+            .actors
+            .iter()
+            .map(|actor| hand_points(actor.get_player().get_hand()))
+            .sum();
+        self.scores[winner] += points;
+    }
+
+    /// Returns the ID of the player with the highest score at or above the target, if any.
+    pub fn get_winner(&self) -> Option<usize> {
+        self.scores
+            .iter()
+            .enumerate()
+            .filter(|(_, score)| **score >= self.target_score)
+            .max_by_key(|(_, score)| **score)
+            .map(|(id, _)| id)
+    }
+
+    pub fn get_scores(&self) -> &[usize] {
+        &self.scores
+    }
+
+    pub fn get_target_score(&self) -> usize {
+        self.target_score
+    }
+
+    /// Returns the ID of the dealer of the next round.
+    pub fn get_dealer(&self) -> usize {
+        self.dealer
+    }
+
+    /// Returns the seed the match was started with, if any.
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns the seed the deck of the next round is shuffled with, if any.
+    pub fn get_round_seed(&self) -> Option<u64> {
+        self.seed.map(|s| s.wrapping_add(self.round))
+    }
+
+    /// Lets the actor at `actor_index` take their turns back with `actor::Command::Undo`
+    /// in every round.
+    pub fn enable_undo(&mut self, actor_index: usize) {
+        self.undo_actors.push(actor_index);
+        if let Some(game) = self.game.as_mut() {
+            game.enable_undo(actor_index);
+        }
+    }
+
+    /// Returns the indexes of the actors who can take their turns back.
+    pub fn get_undo_actors(&self) -> &[usize] {
+        &self.undo_actors
+    }
+
+    /// Tells whether a round was suspended before it ended.
+    pub fn is_suspended(&self) -> bool {
+        self.game.is_some()
+    }
+
+    /// Captures the match, including the round in progress, so that it can be resumed.
+    pub fn snapshot(&self) -> MatchSnapshot {
+        MatchSnapshot {
+            scores: self.scores.clone(),
+            dealer: self.dealer,
+            round: self.round,
+            target_score: self.target_score,
+            rules: self.rules.clone(),
+            seed: self.seed,
+            game: self.game.as_ref().map(|game| game.snapshot()),
+            actor_states: self.actors.iter().map(|actor| actor.save_state()).collect(),
+        }
+    }
+
+    /// Resumes the match captured by `snapshot`, played by `actors` seated as when
+    /// it was captured.
+    pub fn from_snapshot(
+        snapshot: MatchSnapshot,
+        mut actors: Vec<game::GameActor>,
+    ) -> Result<Self, String> {
+        if actors.len() != snapshot.scores.len() {
+            return Err(format!(
+                "The match was saved with {} players, not {}",
+                snapshot.scores.len(),
+                actors.len()
+            ));
+        }
+        if snapshot.dealer >= actors.len() {
+            return Err(format!(
+                "The match was saved with player {} dealing, but only has {} players",
+                snapshot.dealer,
+                actors.len()
+            ));
+        }
+        if snapshot.game.is_none() && snapshot.actor_states.len() != actors.len() {
+            return Err(format!(
+                "The match was saved with the state of {} players, not {}",
+                snapshot.actor_states.len(),
+                actors.len()
+            ));
+        }
+        let game = match snapshot.game {
+            Some(game) => Some(game::Game::from_snapshot(
+                game,
+                std::mem::take(&mut actors),
+            )?),
+            None => {
+                for (actor, state) in actors.iter_mut().zip(snapshot.actor_states) {
+                    actor.load_state(state)?;
+                }
+                None
+            }
+        };
+        Ok(Match {
+            actors,
+            game,
+            scores: snapshot.scores,
+            dealer: snapshot.dealer,
+            round: snapshot.round,
+            target_score: snapshot.target_score,
+            rules: snapshot.rules,
+            seed: snapshot.seed,
+            undo_actors: Vec::new(),
+        })
+    }
+
+    pub fn new(
+        actors: Vec<game::GameActor>,
+        rules: rules::Rules,
+        target_score: usize,
+        seed: Option<u64>,
+    ) -> Self {
+        let num_of_players = actors.len();
+        Match {
+            actors,
+            game: None,
+            scores: vec![0; num_of_players],
+            dealer: num_of_players - 1,
+            round: 0,
+            target_score,
+            rules,
+            seed,
+            undo_actors: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Actor;
+    use crate::ai::AiActor;
+    use crate::card::Colour;
+    use crate::strategy::NormalStrategy;
+
+    fn card(colour: Colour, value: Value) -> Card {
+        Card { colour, value }
+    }
+
+    fn create_test_match(hands: Vec<Vec<Card>>, target_score: usize) -> Match {
+        let actors = hands
+            .into_iter()
+            .enumerate()
+            .map(|(id, hand)| {
+                let mut actor = AiActor::new(id, Box::new(NormalStrategy), Some(0));
+                hand.into_iter()
+                    .for_each(|c| actor.get_player_mut().take_card(c));
+                Box::new(actor) as game::GameActor
+            })
+            .collect();
+        Match::new(actors, rules::Rules::default(), target_score, Some(0))
+    }
+
+    #[test]
+    fn test_card_points() {
+        assert_eq!(card_points(&card(Colour::Red, Value::Number(7))), 7);
+        assert_eq!(card_points(&card(Colour::Red, Value::Skip)), 20);
+        assert_eq!(card_points(&card(Colour::Red, Value::Reverse)), 20);
+        assert_eq!(card_points(&card(Colour::Red, Value::DrawTwo)), 20);
+        assert_eq!(card_points(&card(Colour::Wild, Value::Wild)), 50);
+        assert_eq!(card_points(&card(Colour::Wild, Value::WildDraw(4))), 50);
+    }
+
+    #[test]
+    fn test_hand_points() {
+        let hand = [
+            card(Colour::Blue, Value::Number(3)),
+            card(Colour::Green, Value::Skip),
+            card(Colour::Wild, Value::Wild),
+        ];
+        assert_eq!(hand_points(&hand), 73);
+        assert_eq!(hand_points(&[]), 0);
+    }
+
+    #[test]
+    fn test_record_round_awards_opponents_cards() {
+        let mut uno_match = create_test_match(
+            vec![
+                vec![],
+                vec![card(Colour::Red, Value::Number(9))],
+                vec![card(Colour::Wild, Value::Wild)],
+            ],
+            500,
+        );
+        uno_match.record_round(0);
+        assert_eq!(uno_match.scores, vec![59, 0, 0]);
+        assert_eq!(uno_match.get_winner(), None);
+    }
+
+    #[test]
+    fn test_play_round_scores_the_winner() {
+        let mut uno_match = create_test_match(vec![vec![], vec![], vec![]], 500);
+        let winner = uno_match.play_round().unwrap().unwrap();
+        assert!(uno_match.get_scores()[winner] > 0);
+        assert_eq!(
+            uno_match.get_scores().iter().sum::<usize>(),
+            uno_match.scores[winner]
+        );
+        assert_eq!(uno_match.dealer, 0);
+    }
+
+    #[test]
+    fn test_get_winner_reaches_target() {
+        let mut uno_match =
+            create_test_match(vec![vec![card(Colour::Wild, Value::Wild)], vec![]], 50);
+        uno_match.record_round(1);
+        assert_eq!(uno_match.get_winner(), Some(1));
+    }
+
+    #[test]
+    fn test_snapshot_keeps_the_scores() {
+        let mut uno_match =
+            create_test_match(vec![vec![], vec![card(Colour::Red, Value::Skip)]], 500);
+        uno_match.record_round(0);
+        let json = serde_json::to_string(&uno_match.snapshot()).unwrap();
+
+        let snapshot: MatchSnapshot = serde_json::from_str(&json).unwrap();
+        let actors = create_test_match(vec![vec![], vec![]], 100).actors;
+        let restored = Match::from_snapshot(snapshot, actors).unwrap();
+        assert_eq!(restored.get_scores(), &[20, 0]);
+        assert_eq!(restored.get_target_score(), 500);
+        assert!(!restored.is_suspended());
+    }
+
+    #[test]
+    fn test_snapshot_with_unknown_players_is_rejected() {
+        let uno_match = create_test_match(vec![vec![], vec![]], 500);
+        let actors = || create_test_match(vec![vec![], vec![]], 500).actors;
+
+        let mut snapshot = uno_match.snapshot();
+        snapshot.dealer = 2;
+        assert!(Match::from_snapshot(snapshot, actors()).is_err());
+
+        let mut snapshot = uno_match.snapshot();
+        snapshot.actor_states.pop();
+        assert!(Match::from_snapshot(snapshot, actors()).is_err());
+    }
+}
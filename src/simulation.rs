@@ -0,0 +1,218 @@
+use crate::flow::GameFlow;
+use crate::{game, mcts, rules, strategy};
+use rand::Rng;
+use std::time::Duration;
+
+/// Number of game phases after which a game is abandoned, e.g. when every card is
+/// in the players' hands and nobody can play.
+pub const MAX_GAME_PHASES: usize = 100_000;
+
+/// Settings of a batch of games between AI players and bots, without a human player.
+pub struct Simulation {
+    /// Difficulty of the AI in every seat.
+    pub ai_levels: Vec<strategy::Difficulty>,
+    /// Commands of the external bots seated after the AI players.
+    pub bots: Vec<String>,
+    pub bot_timeout: Duration,
+    pub rules: rules::Rules,
+    pub search_budget: mcts::SearchBudget,
+    pub num_of_games: usize,
+    pub num_of_threads: usize,
+    /// Seed of the first game, the others use the following seeds.
+    pub seed: Option<u64>,
+}
+
+/// Results of a batch of games without a human player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Name of the strategy in every seat.
+    pub seats: Vec<String>,
+    pub num_of_games: usize,
+    /// Number of games won by each seat.
+    pub wins: Vec<usize>,
+    /// Number of games abandoned without a winner.
+    pub unfinished: usize,
+    /// Number of turns played in all the games.
+    pub turns: usize,
+    /// Number of draw pile refills in all the games.
+    pub refills: usize,
+}
+
+impl Report {
+    fn new(seats: &[String]) -> Self {
+        Report {
+            seats: seats.to_vec(),
+            num_of_games: 0,
+            wins: vec![0; seats.len()],
+            unfinished: 0,
+            turns: 0,
+            refills: 0,
+        }
+    }
+
+    fn merge(&mut self, other: &Report) {
+        self.num_of_games += other.num_of_games;
+        for (wins, other_wins) in self.wins.iter_mut().zip(&other.wins) {
+            *wins += other_wins;
+        }
+        self.unfinished += other.unfinished;
+        self.turns += other.turns;
+        self.refills += other.refills;
+    }
+
+    /// Returns the share of the games won by `seat`.
+    pub fn win_rate(&self, seat: usize) -> f64 {
+        self.wins[seat] as f64 / self.num_of_games.max(1) as f64
+    }
+
+    /// Returns the share of the games won by every seat played by the strategy `name`,
+    /// divided by the number of those seats.
+    pub fn win_rate_of(&self, name: &str) -> f64 {
+        let seats: Vec<usize> = (0..self.seats.len())
+            .filter(|seat| self.seats[*seat] == name)
+            .collect();
+        let wins: usize = seats.iter().map(|seat| self.wins[*seat]).sum();
+        wins as f64 / (self.num_of_games.max(1) * seats.len().max(1)) as f64
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.turns as f64 / self.num_of_games.max(1) as f64
+    }
+
+    pub fn average_refills(&self) -> f64 {
+        self.refills as f64 / self.num_of_games.max(1) as f64
+    }
+}
+
+impl Simulation {
+    /// Plays every game and collects the results. The games are split evenly across
+    /// the threads, and every game has its own seed so the results do not depend on
+    /// the number of threads. Fails before playing if the seats cannot play a game
+    /// under the rules, e.g. there are fewer than 2, or if a bot cannot be started.
+    pub fn run(&self) -> Result<Report, String> {
+        self.rules
+            .check_game_attributes(self.ai_levels.len() + self.bots.len())?;
+        let base_seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let num_of_threads = self.num_of_threads.clamp(1, self.num_of_games.max(1));
+        let seats = self.seat_names();
+        let mut report = Report::new(&seats);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..num_of_threads)
+                .map(|thread| {
+                    let seats = &seats;
+                    scope.spawn(move || {
+                        let mut report = Report::new(seats);
+                        for game in (thread..self.num_of_games).step_by(num_of_threads) {
+                            let seed = base_seed.wrapping_add(game as u64);
+                            self.play_game(game, seed, &mut report)?;
+                        }
+                        Ok::<Report, String>(report)
+                    })
+                })
+                .collect();
+            for handle in handles {
+                report.merge(&handle.join().expect("A simulation thread panicked")?);
+            }
+            Ok(report)
+        })
+    }
+
+    fn seat_names(&self) -> Vec<String> {
+        let ai_names = self
+            .ai_levels
+            .iter()
+            .map(|difficulty| difficulty.to_string());
+        let bot_names = self.bots.iter().map(|command| format!("bot {command}"));
+        ai_names.chain(bot_names).collect()
+    }
+
+    fn play_game(&self, game_number: usize, seed: u64, report: &mut Report) -> Result<(), String> {
+        let mut actors: Vec<game::GameActor> = self
+            .ai_levels
+            .iter()
+            .enumerate()
+            .map(|(id, difficulty)| {
+                game::create_ai_actor(id, *difficulty, &self.rules, self.search_budget, Some(seed))
+            })
+            .collect();
+        for command in &self.bots {
+            let id = actors.len();
+            actors.push(game::create_bot_actor(
+                id,
+                command,
+                self.bot_timeout,
+                Some(seed),
+            )?);
+        }
+        let mut game = game::Game::new(actors, self.rules.clone(), Some(seed));
+        // The dealer rotates so that every seat plays first equally often
+        game.set_dealer(game_number % report.seats.len());
+        game.play_to_end(MAX_GAME_PHASES);
+
+        report.num_of_games += 1;
+        match game.get_winner() {
+            Some(winner) => report.wins[winner] += 1,
+            None => report.unfinished += 1,
+        }
+        report.turns += game.get_number_of_turns();
+        report.refills += game.get_number_of_refills();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Difficulty;
+
+    fn simulation(num_of_threads: usize) -> Simulation {
+        Simulation {
+            ai_levels: vec![Difficulty::Normal, Difficulty::Easy, Difficulty::Normal],
+            bots: Vec::new(),
+            bot_timeout: Duration::ZERO,
+            rules: rules::Rules::default(),
+            search_budget: mcts::SearchBudget::Iterations(1),
+            num_of_games: 20,
+            num_of_threads,
+            seed: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_every_game_is_reported() {
+        let report = simulation(1).run().unwrap();
+        assert_eq!(report.num_of_games, 20);
+        assert_eq!(report.wins.iter().sum::<usize>() + report.unfinished, 20);
+        assert!(report.turns > 0);
+    }
+
+    #[test]
+    fn test_results_do_not_depend_on_threads() {
+        assert_eq!(simulation(1).run(), simulation(3).run());
+    }
+
+    #[test]
+    fn test_missing_bot_fails() {
+        let mut simulation = simulation(1);
+        simulation.bots = vec![String::from("./no-such-bot")];
+        assert!(simulation.run().is_err());
+    }
+
+    #[test]
+    fn test_single_seat_fails() {
+        let mut simulation = simulation(1);
+        simulation.ai_levels = vec![Difficulty::Easy];
+        assert!(simulation.run().is_err());
+    }
+
+    #[test]
+    fn test_win_rate_of_strategy() {
+        let seats = ["normal", "easy", "normal"].map(String::from);
+        let mut report = Report::new(&seats);
+        report.num_of_games = 10;
+        report.wins = vec![4, 2, 4];
+        assert_eq!(report.win_rate(1), 0.2);
+        assert_eq!(report.win_rate_of("normal"), 0.4);
+        assert_eq!(report.win_rate_of("hard"), 0.0);
+    }
+}
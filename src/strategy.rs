@@ -0,0 +1,498 @@
+use crate::{actor, card, counting, mcts, observation, rules, score};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// How well an AI player plays.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// Plays a random legal move.
+    Easy,
+    /// Plays the first legal card.
+    Normal,
+    /// Holds on to wild cards, dumps high-value cards and targets the leader.
+    Hard,
+    /// Counts the cards played and steers the colour away from the next player.
+    Counting,
+    /// Simulates games to find the move most likely to win.
+    Mcts,
+}
+
+impl Difficulty {
+    /// Every difficulty, from the weakest to the strongest.
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Counting,
+        Difficulty::Mcts,
+    ];
+
+    /// Returns the name the difficulty is given and parsed from on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Counting => "counting",
+            Difficulty::Mcts => "mcts",
+        }
+    }
+
+    /// Creates the strategy of an AI player. `rules` and `budget` are only used by
+    /// strategies that simulate games.
+    pub fn strategy(self, rules: &rules::Rules, budget: mcts::SearchBudget) -> Box<dyn Strategy> {
+        match self {
+            Difficulty::Easy => Box::new(EasyStrategy),
+            Difficulty::Normal => Box::new(NormalStrategy),
+            Difficulty::Hard => Box::new(HardStrategy),
+            Difficulty::Counting => Box::new(counting::CountingStrategy),
+            Difficulty::Mcts => Box::new(mcts::MctsStrategy::new(rules.clone(), budget)),
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+            .ok_or_else(|| format!("There is no difficulty called '{name}'"))
+    }
+}
+
+/// A move picked by a strategy, with the reason the strategy picked it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    /// `None` to draw, or to keep a card that was just drawn.
+    pub action: Option<actor::UserAction>,
+    /// Why the move was picked, e.g. "it gets rid of 9 points".
+    pub reason: Cow<'static, str>,
+}
+
+impl Choice {
+    pub fn new(action: Option<actor::UserAction>, reason: impl Into<Cow<'static, str>>) -> Self {
+        Choice {
+            action,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Decides which move an AI player makes.
+pub trait Strategy {
+    /// Picks one of the `legal_actions` to take with `hand` and tells why.
+    fn choose_action(
+        &mut self,
+        hand: &[card::Card],
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> Choice;
+
+    /// Picks the colour to declare after playing a wild card.
+    fn choose_colour(
+        &mut self,
+        hand: &[card::Card],
+        observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> card::Colour;
+}
+
+pub struct EasyStrategy;
+
+impl Strategy for EasyStrategy {
+    fn choose_action(
+        &mut self,
+        _hand: &[card::Card],
+        legal_actions: &[actor::UserAction],
+        _observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> Choice {
+        let moves: Vec<actor::UserAction> = legal_actions
+            .iter()
+            .filter(|action| **action != actor::UserAction::Uno)
+            .copied()
+            .collect();
+        Choice::new(moves.choose(rng).copied(), "it was picked at random")
+    }
+
+    fn choose_colour(
+        &mut self,
+        _hand: &[card::Card],
+        _observation: &observation::Observation,
+        rng: &mut ChaCha8Rng,
+    ) -> card::Colour {
+        *card::COLOURS.choose(rng).unwrap() // There is always a colour to choose
+    }
+}
+
+pub struct NormalStrategy;
+
+impl Strategy for NormalStrategy {
+    fn choose_action(
+        &mut self,
+        hand: &[card::Card],
+        legal_actions: &[actor::UserAction],
+        _observation: &observation::Observation,
+        _rng: &mut ChaCha8Rng,
+    ) -> Choice {
+        let colour = most_common_colour(hand);
+        let action = legal_actions
+            .iter()
+            .find(|action| match action {
+                actor::UserAction::Play(_) => true,
+                actor::UserAction::PlayWild(_, c) => *c == colour,
+                _ => false,
+            })
+            .copied();
+        let reason = match action {
+            Some(actor::UserAction::PlayWild(..)) => {
+                "it is the first card that can be played, as the colour you hold the most"
+            }
+            Some(_) => "it is the first card that can be played",
+            None => "no card can be played",
+        };
+        Choice::new(action, reason)
+    }
+
+    fn choose_colour(
+        &mut self,
+        hand: &[card::Card],
+        _observation: &observation::Observation,
+        _rng: &mut ChaCha8Rng,
+    ) -> card::Colour {
+        most_common_colour(hand)
+    }
+}
+
+pub struct HardStrategy;
+
+impl HardStrategy {
+    /// Ranks a playable card, the highest ranked card is played.
+    /// Wild cards are kept for last, action cards are played first against the leader,
+    /// and otherwise the cards worth the most points are dumped first.
+    fn rank(card: &card::Card, next_is_leader: bool) -> usize {
+        match card.value {
+            card::Value::WildDraw(_) if next_is_leader => 50,
+            card::Value::Skip | card::Value::DrawTwo if next_is_leader => 40,
+            card::Value::Reverse if next_is_leader => 30,
+            card::Value::Number(_)
+            | card::Value::Skip
+            | card::Value::Reverse
+            | card::Value::DrawTwo => 1 + score::card_points(card),
+            card::Value::Wild | card::Value::WildDraw(_) => 0,
+        }
+    }
+
+    /// Tells why `card` has the highest rank.
+    fn reason(
+        card: &card::Card,
+        next_is_leader: bool,
+        observation: &observation::Observation,
+    ) -> Cow<'static, str> {
+        let next_hand_size = observation.next_hand_size().unwrap_or(0);
+        match card.value {
+            _ if card.is_draw_card() && observation.pending_penalty > 0 => Cow::Owned(format!(
+                "it passes the +{} penalty on",
+                observation.pending_penalty
+            )),
+            card::Value::WildDraw(_)
+            | card::Value::Skip
+            | card::Value::DrawTwo
+            | card::Value::Reverse
+                if next_is_leader =>
+            {
+                Cow::Owned(format!(
+                    "it holds back the next player, who leads with {next_hand_size} cards"
+                ))
+            }
+            card::Value::Wild | card::Value::WildDraw(_) => {
+                Cow::Borrowed("wild cards are held until no other card can be played")
+            }
+            _ => Cow::Owned(format!(
+                "it gets rid of {} points, the most of the playable cards",
+                score::card_points(card)
+            )),
+        }
+    }
+}
+
+impl Strategy for HardStrategy {
+    fn choose_action(
+        &mut self,
+        hand: &[card::Card],
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+        _rng: &mut ChaCha8Rng,
+    ) -> Choice {
+        let colour = most_common_colour(hand);
+        let next_is_leader = observation.next_is_leader();
+        let best = legal_actions
+            .iter()
+            .filter_map(|action| match action {
+                actor::UserAction::Play(i) => Some((*i, *action)),
+                actor::UserAction::PlayWild(i, c) if *c == colour => Some((*i, *action)),
+                _ => None,
+            })
+            .filter_map(|(i, action)| hand.get(i).map(|card| (card, action)))
+            .rev() // Prefer the first card when ranks are equal
+            .max_by_key(|(card, _)| HardStrategy::rank(card, next_is_leader));
+        match best {
+            Some((card, action)) => Choice::new(
+                Some(action),
+                HardStrategy::reason(card, next_is_leader, observation),
+            ),
+            None => Choice::new(None, "no card can be played"),
+        }
+    }
+
+    fn choose_colour(
+        &mut self,
+        hand: &[card::Card],
+        _observation: &observation::Observation,
+        _rng: &mut ChaCha8Rng,
+    ) -> card::Colour {
+        most_common_colour(hand)
+    }
+}
+
+/// Finds the colour the hand holds the most cards of.
+pub fn most_common_colour(hand: &[card::Card]) -> card::Colour {
+    *card::COLOURS
+        .iter()
+        .max_by_key(|colour| hand.iter().filter(|card| card.colour == **colour).count())
+        .unwrap() // The iterator is never empty
+}
+
+/// Describes `action` for a player holding `hand`, e.g. "play Wild as Red".
+pub fn describe(
+    hand: &[card::Card],
+    action: actor::UserAction,
+    observation: &observation::Observation,
+) -> String {
+    match action {
+        actor::UserAction::Draw if observation.pending_penalty > 0 => {
+            format!("draw {} cards", observation.pending_penalty)
+        }
+        actor::UserAction::Draw => String::from("draw a card"),
+        actor::UserAction::Pass => String::from("keep the drawn card"),
+        actor::UserAction::Uno => String::from("declare UNO"),
+        actor::UserAction::Play(i) | actor::UserAction::PlayWild(i, _) => {
+            let Some(card) = hand.get(i) else {
+                return String::from("play a card that is not in the hand");
+            };
+            match action {
+                actor::UserAction::PlayWild(_, colour) => format!("play {card} as {colour}"),
+                _ => format!("play {card}"),
+            }
+        }
+    }
+}
+
+/// Describes the move a strategy chose and the reason it gives, e.g. "play Red 9: it
+/// gets rid of 9 points". `None` is taken to draw, or to keep a card that was just drawn.
+pub fn describe_choice(
+    hand: &[card::Card],
+    legal_actions: &[actor::UserAction],
+    choice: &Choice,
+    observation: &observation::Observation,
+) -> String {
+    let action = choice.action.unwrap_or_else(|| {
+        if legal_actions.contains(&actor::UserAction::Pass) {
+            actor::UserAction::Pass
+        } else {
+            actor::UserAction::Draw
+        }
+    });
+    format!("{}: {}", describe(hand, action, observation), choice.reason)
+}
+
+/// The reason to declare UNO, which players do before any strategy picks their move.
+pub const UNO_REASON: &str = "you are about to play down to one card";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::UserAction;
+    use crate::card::{Card, Colour, Value};
+    use crate::deck;
+
+    fn card(colour: Colour, value: Value) -> Card {
+        Card { colour, value }
+    }
+
+    fn observe(opponent_hand_sizes: &[usize]) -> observation::Observation {
+        let top_card = card(Colour::Red, Value::Number(0));
+        observation::create_test_observation(top_card, opponent_hand_sizes)
+    }
+
+    #[test]
+    fn test_difficulty_is_parsed_from_its_name() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.to_string().parse(), Ok(difficulty));
+        }
+        assert!("Mcts".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_easy_plays_a_legal_move() {
+        let legal_actions = [UserAction::Play(1), UserAction::Draw, UserAction::Uno];
+        let mut rng = deck::new_rng(Some(0));
+        for _ in 0..20 {
+            let action = EasyStrategy
+                .choose_action(&[], &legal_actions, &observe(&[]), &mut rng)
+                .action;
+            assert!(matches!(
+                action,
+                Some(UserAction::Play(1)) | Some(UserAction::Draw)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_normal_plays_first_legal_card() {
+        let hand = [
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Red, Value::Number(9)),
+        ];
+        let legal_actions = [UserAction::Play(0), UserAction::Play(1), UserAction::Draw];
+        let mut rng = deck::new_rng(Some(0));
+        let action = NormalStrategy
+            .choose_action(&hand, &legal_actions, &observe(&[7]), &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::Play(0)));
+    }
+
+    #[test]
+    fn test_normal_draws_without_legal_card() {
+        let mut rng = deck::new_rng(Some(0));
+        let action = NormalStrategy
+            .choose_action(&[], &[UserAction::Draw], &observe(&[7]), &mut rng)
+            .action;
+        assert_eq!(action, None);
+    }
+
+    #[test]
+    fn test_hard_holds_wild_cards() {
+        let hand = [
+            card(Colour::Wild, Value::Wild),
+            card(Colour::Red, Value::Number(1)),
+        ];
+        let legal_actions = [
+            UserAction::PlayWild(0, Colour::Red),
+            UserAction::Play(1),
+            UserAction::Draw,
+        ];
+        let mut rng = deck::new_rng(Some(0));
+        let action = HardStrategy
+            .choose_action(&hand, &legal_actions, &observe(&[7]), &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::Play(1)));
+    }
+
+    #[test]
+    fn test_hard_dumps_high_value_cards() {
+        let hand = [
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Red, Value::Number(9)),
+            card(Colour::Red, Value::Number(4)),
+        ];
+        let legal_actions = [
+            UserAction::Play(0),
+            UserAction::Play(1),
+            UserAction::Play(2),
+        ];
+        let mut rng = deck::new_rng(Some(0));
+        let action = HardStrategy
+            .choose_action(&hand, &legal_actions, &observe(&[7]), &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::Play(1)));
+    }
+
+    #[test]
+    fn test_hard_dumps_action_cards() {
+        let hand = [
+            card(Colour::Red, Value::Number(9)),
+            card(Colour::Red, Value::Skip),
+        ];
+        let legal_actions = [UserAction::Play(0), UserAction::Play(1)];
+        let mut rng = deck::new_rng(Some(0));
+        let action = HardStrategy
+            .choose_action(&hand, &legal_actions, &observe(&[5, 2]), &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::Play(1)));
+    }
+
+    #[test]
+    fn test_hard_targets_the_leader() {
+        let hand = [
+            card(Colour::Red, Value::Number(9)),
+            card(Colour::Wild, Value::WildDraw(4)),
+        ];
+        let legal_actions = [UserAction::Play(0), UserAction::PlayWild(1, Colour::Red)];
+        let mut rng = deck::new_rng(Some(0));
+        let action = HardStrategy
+            .choose_action(&hand, &legal_actions, &observe(&[2, 5]), &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::PlayWild(1, Colour::Red)));
+        let action = HardStrategy
+            .choose_action(&hand, &legal_actions, &observe(&[5, 2]), &mut rng)
+            .action;
+        assert_eq!(action, Some(UserAction::Play(0)));
+    }
+
+    #[test]
+    fn test_most_common_colour() {
+        let hand = [
+            card(Colour::Green, Value::Number(1)),
+            card(Colour::Blue, Value::Number(1)),
+            card(Colour::Green, Value::Skip),
+            card(Colour::Wild, Value::Wild),
+        ];
+        assert_eq!(most_common_colour(&hand), Colour::Green);
+    }
+
+    #[test]
+    fn test_strategies_explain_their_choices() {
+        let hand = [
+            card(Colour::Red, Value::Number(1)),
+            card(Colour::Red, Value::Number(9)),
+        ];
+        let legal_actions = [UserAction::Play(0), UserAction::Play(1), UserAction::Draw];
+        let observation = observe(&[7]);
+        let mut rng = deck::new_rng(Some(0));
+        let choice = HardStrategy.choose_action(&hand, &legal_actions, &observation, &mut rng);
+        assert_eq!(
+            describe_choice(&hand, &legal_actions, &choice, &observation),
+            "play Red 9: it gets rid of 9 points, the most of the playable cards"
+        );
+        let choice = NormalStrategy.choose_action(&hand, &legal_actions, &observation, &mut rng);
+        assert_eq!(
+            describe_choice(&hand, &legal_actions, &choice, &observation),
+            "play Red 1: it is the first card that can be played"
+        );
+        let choice = EasyStrategy.choose_action(&hand, &legal_actions, &observation, &mut rng);
+        assert_eq!(choice.reason, "it was picked at random");
+    }
+
+    #[test]
+    fn test_describe_choice_to_keep_the_drawn_card() {
+        let hand = [card(Colour::Blue, Value::Number(7))];
+        let legal_actions = [UserAction::Play(0), UserAction::Pass];
+        let choice = Choice::new(None, "it is worth saving for later");
+        assert_eq!(
+            describe_choice(&hand, &legal_actions, &choice, &observe(&[5])),
+            "keep the drawn card: it is worth saving for later"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{mcts, rules, simulation, strategy};
+use std::time::Duration;
+
+/// Rating of the average strategy in the tournament.
+const BASE_ELO: f64 = 1500.0;
+
+/// Wins added to every strategy when rating it, so that a strategy that never won
+/// still gets a finite rating.
+const PRIOR_WINS: f64 = 0.5;
+
+/// z-score of the 95% confidence interval of the win rates.
+const Z_95: f64 = 1.96;
+
+/// Settings of a round-robin tournament between AI strategies.
+///
+/// Every strategy sits at the table in every game, and the games are repeated for
+/// every seating permutation with the same seeds, so no strategy is favoured by its
+/// seat or by the cards it is dealt.
+pub struct Tournament {
+    pub strategies: Vec<strategy::Difficulty>,
+    pub rules: rules::Rules,
+    pub search_budget: mcts::SearchBudget,
+    pub games_per_seating: usize,
+    pub num_of_threads: usize,
+    pub seed: u64,
+}
+
+/// Results of a strategy in a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub difficulty: strategy::Difficulty,
+    pub games: usize,
+    pub wins: usize,
+    /// 95% Wilson score interval of the win rate.
+    pub confidence_interval: (f64, f64),
+    pub elo: f64,
+}
+
+impl Standing {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+}
+
+impl Tournament {
+    /// Checks that the tournament can be played with the rules.
+    pub fn check(&self) -> Result<(), String> {
+        if self.strategies.len() < 2 {
+            return Err(String::from("A tournament needs at least 2 strategies"));
+        }
+        for (i, difficulty) in self.strategies.iter().enumerate() {
+            if self.strategies[..i].contains(difficulty) {
+                return Err(format!(
+                    "The strategy {difficulty} is listed more than once"
+                ));
+            }
+        }
+        self.rules.check_game_attributes(self.strategies.len())
+    }
+
+    /// Plays every seating permutation and returns the standings, best first.
+    pub fn run(&self) -> Result<Vec<Standing>, String> {
+        let n = self.strategies.len();
+        let mut wins = vec![0; n];
+        let mut games = 0;
+
+        for seating in permutations(n) {
+            let report = simulation::Simulation {
+                ai_levels: seating.iter().map(|i| self.strategies[*i]).collect(),
+                bots: Vec::new(),
+                bot_timeout: Duration::ZERO,
+                rules: self.rules.clone(),
+                search_budget: self.search_budget,
+                num_of_games: self.games_per_seating,
+                num_of_threads: self.num_of_threads,
+                seed: Some(self.seed),
+            }
+            .run()?;
+
+            games += report.num_of_games;
+            for (seat, i) in seating.iter().enumerate() {
+                wins[*i] += report.wins[seat];
+            }
+        }
+
+        let elo = fit_elo(&wins);
+        let mut standings: Vec<Standing> = self
+            .strategies
+            .iter()
+            .enumerate()
+            .map(|(i, difficulty)| Standing {
+                difficulty: *difficulty,
+                games,
+                wins: wins[i],
+                confidence_interval: wilson_interval(wins[i], games),
+                elo: elo[i],
+            })
+            .collect();
+        standings.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+        Ok(standings)
+    }
+}
+
+/// Returns every ordering of `0..n`.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for permutation in permutations(n - 1) {
+        for position in 0..=permutation.len() {
+            let mut permutation = permutation.clone();
+            permutation.insert(position, n - 1);
+            result.push(permutation);
+        }
+    }
+    result
+}
+
+/// Returns the 95% Wilson score interval of a win rate.
+fn wilson_interval(wins: usize, games: usize) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = wins as f64 / n;
+    let z2 = Z_95 * Z_95;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((centre - margin).max(0.0), (centre + margin).min(1.0))
+}
+
+/// Fits Elo ratings to the number of games won by every strategy, all of them having
+/// played the same games. The ratings average `BASE_ELO`.
+///
+/// In the Elo model a player wins against the table with a probability proportional
+/// to `10^(elo / 400)`, so the best fitting rating is proportional to the log of the
+/// number of wins.
+fn fit_elo(wins: &[usize]) -> Vec<f64> {
+    let elo: Vec<f64> = wins
+        .iter()
+        .map(|wins| 400.0 * (*wins as f64 + PRIOR_WINS).log10())
+        .collect();
+    let mean = elo.iter().sum::<f64>() / elo.len().max(1) as f64;
+    elo.iter().map(|rating| rating - mean + BASE_ELO).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Difficulty;
+
+    fn tournament(strategies: Vec<Difficulty>) -> Tournament {
+        Tournament {
+            strategies,
+            rules: rules::Rules::default(),
+            search_budget: mcts::SearchBudget::Iterations(1),
+            games_per_seating: 20,
+            num_of_threads: 2,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_permutations() {
+        let mut permutations = permutations(3);
+        permutations.sort();
+        assert_eq!(
+            permutations,
+            vec![
+                vec![0, 1, 2],
+                vec![0, 2, 1],
+                vec![1, 0, 2],
+                vec![1, 2, 0],
+                vec![2, 0, 1],
+                vec![2, 1, 0],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        let (low, high) = wilson_interval(50, 100);
+        assert!((low - 0.4038).abs() < 1e-3);
+        assert!((high - 0.5962).abs() < 1e-3);
+        assert_eq!(wilson_interval(0, 10).0, 0.0);
+    }
+
+    #[test]
+    fn test_elo_matches_win_ratio() {
+        // Winning about 10 times as often is worth 400 Elo points
+        let elo = fit_elo(&[209, 20, 0]);
+        assert!((elo[0] - elo[1] - 400.0).abs() < 5.0);
+        assert!(elo[2].is_finite() && elo[2] < elo[1]);
+        assert!((elo.iter().sum::<f64>() - 3.0 * BASE_ELO).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_check_rejects_repeated_strategies() {
+        assert!(tournament(vec![Difficulty::Easy]).check().is_err());
+        assert!(tournament(vec![Difficulty::Easy, Difficulty::Easy])
+            .check()
+            .is_err());
+        assert!(tournament(vec![Difficulty::Easy, Difficulty::Hard])
+            .check()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_stronger_strategy_ranks_first() {
+        let standings = tournament(vec![Difficulty::Easy, Difficulty::Normal])
+            .run()
+            .unwrap();
+        assert_eq!(standings[0].difficulty, Difficulty::Normal);
+        assert_eq!(standings[0].games, 40);
+        assert!(standings[0].elo > standings[1].elo);
+        assert!(standings[0].wins + standings[1].wins <= 40);
+    }
+}
@@ -1,15 +1,44 @@
-use crate::{actor, card, deck::DeckTrait, player};
+use rand_chacha::ChaCha8Rng;
 use std::io;
+use uno::{
+    actor, card, deck, event, observation, player, replay, score, simulation, strategy, tournament,
+};
 
 const DRAW: &str = "d";
+const UNO: &str = "uno";
+const PASS: &str = "p";
+const HINT: &str = "hint";
+const SAVE: &str = "save";
+const UNDO: &str = "undo";
 const R: &str = "r";
 const G: &str = "g";
 const B: &str = "b";
 const Y: &str = "y";
+const NEXT: &str = "n";
+const BACK: &str = "b";
+const QUIT: &str = "q";
+const YES: &str = "y";
+const NO: &str = "n";
 
 pub struct HumanActor {
     id: usize,
     player: player::Player,
+    /// Strategy asked for a hint when the player enters `HINT`.
+    hint_strategy: Box<dyn strategy::Strategy>,
+    rng: ChaCha8Rng,
+    /// Keeps the moves of the other players on screen instead of clearing it every turn.
+    is_verbose: bool,
+    /// Offers to take turns back with `UNDO`, in practice games that keep an undo history.
+    is_practice: bool,
+    /// Command entered instead of the last move, for the game to take.
+    command: Option<actor::Command>,
+}
+
+/// What the player enters when asked for their move.
+#[derive(Debug, PartialEq)]
+enum TurnInput {
+    Action(actor::UserAction),
+    Command(actor::Command),
 }
 
 impl actor::Actor for HumanActor {
@@ -24,30 +53,143 @@ impl actor::Actor for HumanActor {
         &mut self.player
     }
 
-    fn get_turn_action(&mut self) -> actor::UserAction {
-        get_user_turn_action()
+    fn get_turn_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        print_game_context(self.id, observation, self.is_verbose);
+        print_player_context(&self.player, self.is_practice);
+        print_playable_cards(legal_actions);
+        self.read_action(legal_actions, observation)
+    }
+
+    fn get_drawn_card_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        let card_index = self.player.get_number_of_cards().saturating_sub(1);
+        if let Ok(card) = self.player.get_card(card_index) {
+            println!("You drew {card}");
+        }
+        println!("{card_index:02}: Play it");
+        println!("{PASS:02}: Keep it");
+        println!("{UNO}: Declare UNO");
+        println!("{HINT}: Ask the AI for a hint");
+        println!("{SAVE}: Save the match and quit");
+        if self.is_practice {
+            println!("{UNDO}: Take back your last turn");
+        }
+        self.read_action(legal_actions, observation)
+    }
+
+    fn get_stack_action(
+        &mut self,
+        penalty: usize,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        print_game_context(self.id, observation, self.is_verbose);
+        print_player_context(&self.player, self.is_practice);
+        println!("Stack a draw card or enter '{DRAW}' to take the +{penalty} penalty");
+        print_playable_cards(legal_actions);
+        self.read_action(legal_actions, observation)
+    }
+
+    fn decide_challenge(&mut self) -> bool {
+        println!("A Wild Draw card was played on you. Challenge it? ({YES}/{NO})");
+        get_user_confirmation()
+    }
+
+    fn catch_missed_uno(&mut self, offender_id: usize) -> bool {
+        println!("Player {offender_id} has one card left and did not declare UNO! Catch them? ({YES}/{NO})");
+        get_user_confirmation()
+    }
+
+    fn decide_jump_in(&mut self, card_index: usize) -> bool {
+        if let Ok(card) = self.player.get_card(card_index) {
+            println!("You can jump in with {card}. Play it? ({YES}/{NO})");
+        }
+        get_user_confirmation()
     }
 
-    fn get_color_choice(&mut self) -> card::Colour {
+    fn get_swap_target(&mut self, hand_sizes: &[usize]) -> usize {
+        println!("Choose a player to swap hands with:");
+        for (id, size) in hand_sizes.iter().enumerate() {
+            if id != self.id {
+                println!("{id}: Player {id} ({size} cards)");
+            }
+        }
+        get_user_index()
+    }
+
+    fn get_color_choice(&mut self, _observation: &observation::Observation) -> card::Colour {
+        print_hand(self.player.get_hand());
         get_user_wild_colour()
     }
 
     fn pre_turn_action(&mut self) {
-        print_player_context(&self.player);
+        // Do nothing
     }
 
     fn post_turn_action(&mut self) {
         // Do nothing
     }
+
+    fn take_command(&mut self) -> Option<actor::Command> {
+        self.command.take()
+    }
 }
 
 impl HumanActor {
-    pub fn new(id: usize) -> HumanActor {
+    pub fn new(id: usize, is_verbose: bool, is_practice: bool) -> HumanActor {
         HumanActor {
             id,
             player: player::Player::new(),
+            hint_strategy: Box::new(strategy::HardStrategy),
+            rng: deck::new_rng(None),
+            is_verbose,
+            is_practice,
+            command: None,
         }
     }
+
+    /// Reads the player's move. A command entered instead is kept for the game to take,
+    /// and the move returned with it is ignored.
+    fn read_action(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) -> actor::UserAction {
+        let is_practice = self.is_practice;
+        match get_user_turn_action(|| self.print_hint(legal_actions, observation), is_practice) {
+            TurnInput::Action(action) => action,
+            TurnInput::Command(command) => {
+                self.command = Some(command);
+                actor::UserAction::Draw
+            }
+        }
+    }
+
+    /// Prints the move the hint strategy would make and why.
+    fn print_hint(
+        &mut self,
+        legal_actions: &[actor::UserAction],
+        observation: &observation::Observation,
+    ) {
+        let hand = self.player.get_hand();
+        let hint = if legal_actions.contains(&actor::UserAction::Uno) {
+            let action = strategy::describe(hand, actor::UserAction::Uno, observation);
+            format!("{action}: {}", strategy::UNO_REASON)
+        } else {
+            let choice =
+                self.hint_strategy
+                    .choose_action(hand, legal_actions, observation, &mut self.rng);
+            strategy::describe_choice(hand, legal_actions, &choice, observation)
+        };
+        println!("Hint: {hint}");
+    }
 }
 
 fn clear_terminal() {
@@ -55,31 +197,93 @@ fn clear_terminal() {
     print!("{}[H", 27 as char); // Move the cursor to the top-left corner
 }
 
-pub fn get_game_context(player_index: usize, deck: &impl DeckTrait) {
-    clear_terminal();
-    println!("Player {player_index}'s turn");
+/// Prints what the human player can see of the game at the start of their turn.
+/// The terminal is cleared first unless `keep_log` is set.
+fn print_game_context(player_id: usize, observation: &observation::Observation, keep_log: bool) {
+    if !keep_log {
+        clear_terminal();
+    }
+    println!("Player {player_id}'s turn");
     println!(
         "Number of cards in the draw pile: {}",
-        deck.number_of_cards_in_draw_pile()
+        observation.draw_pile_size
     );
-    if let Ok(card) = deck.get_top_card() {
-        println!("card on top: {card}");
+    println!("card on top: {}", observation.top_card);
+    if let Some(colour) = observation.declared_colour {
+        println!("colour in play: {colour}");
+    }
+    let direction = if observation.is_flow_clockwise {
+        "clockwise"
     } else {
-        print!("No card on top... somehow...");
+        "counterclockwise"
+    };
+    println!("Play goes {direction}");
+    let hand_sizes: Vec<String> = observation
+        .opponent_hand_sizes
+        .iter()
+        .map(|size| size.to_string())
+        .collect();
+    println!("Cards of the next players: {}", hand_sizes.join(", "));
+    let last_card_played = observation
+        .history
+        .iter()
+        .rev()
+        .find_map(|record| match record {
+            observation::PlayRecord::CardPlayed { player, card } => Some((player, card)),
+            _ => None,
+        });
+    if let Some((player, card)) = last_card_played {
+        println!("Player {player} played {card}");
     }
 }
 
-fn print_player_context(player: &player::Player) {
-    player.print_hand();
+/// Converts a hand to a string with each card and its index.
+fn hand_to_string(hand: &[card::Card]) -> String {
+    hand.iter()
+        .enumerate()
+        .map(|(i, card)| format!("{i:02}: {card}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn print_hand(hand: &[card::Card]) {
+    println!("{}", hand_to_string(hand));
+}
+
+fn print_player_context(player: &player::Player, is_practice: bool) {
+    print_hand(player.get_hand());
     println!("{DRAW:02}: Draw card");
+    println!("{UNO}: Declare UNO");
+    println!("{HINT}: Ask the AI for a hint");
+    println!("{SAVE}: Save the match and quit");
+    if is_practice {
+        println!("{UNDO}: Take back your last turn");
+    }
 }
 
-pub fn get_user_turn_action() -> actor::UserAction {
+fn print_playable_cards(legal_actions: &[actor::UserAction]) {
+    let mut indexes: Vec<usize> = legal_actions
+        .iter()
+        .filter_map(|action| match action {
+            actor::UserAction::Play(i) | actor::UserAction::PlayWild(i, _) => Some(*i),
+            _ => None,
+        })
+        .collect();
+    indexes.dedup();
+    let indexes: Vec<String> = indexes.iter().map(|i| format!("{i:02}")).collect();
+    println!("Playable cards: {}", indexes.join(", "));
+}
+
+/// Reads the player's move or command, calling `print_hint` every time they ask for a hint.
+/// Taking a turn back is only accepted in practice games.
+fn get_user_turn_action(mut print_hint: impl FnMut(), is_practice: bool) -> TurnInput {
     let mut input = String::new();
 
     loop {
         if io::stdin().read_line(&mut input).is_ok() {
-            if let Ok(action) = check_turn_action_input(&input) {
+            if input.trim() == HINT {
+                print_hint();
+            } else if let Ok(action) = check_turn_action_input(&input, is_practice) {
                 return action;
             }
         }
@@ -87,22 +291,239 @@ pub fn get_user_turn_action() -> actor::UserAction {
     }
 }
 
-fn check_turn_action_input(input: &str) -> Result<actor::UserAction, ()> {
+fn check_turn_action_input(input: &str, is_practice: bool) -> Result<TurnInput, ()> {
     if let Ok(index) = input.trim().parse::<usize>() {
-        return Ok(actor::UserAction::Play(index));
-    } else if let Ok(str) = input.trim().parse::<String>() {
-        if str == DRAW {
-            return Ok(actor::UserAction::Draw);
-        }
+        return Ok(TurnInput::Action(actor::UserAction::Play(index)));
+    } else if input.trim() == DRAW {
+        return Ok(TurnInput::Action(actor::UserAction::Draw));
+    } else if input.trim() == UNO {
+        return Ok(TurnInput::Action(actor::UserAction::Uno));
+    } else if input.trim() == PASS {
+        return Ok(TurnInput::Action(actor::UserAction::Pass));
+    } else if input.trim() == SAVE {
+        return Ok(TurnInput::Command(actor::Command::Suspend));
+    } else if input.trim() == UNDO && is_practice {
+        return Ok(TurnInput::Command(actor::Command::Undo));
     }
 
     Err(())
 }
 
+pub fn print_ai_rationale(id: usize, rationale: &str) {
+    println!("Player {id}: {rationale}");
+}
+
 pub fn announce_winner(id: usize) {
     println!("Player {id} wins!");
 }
 
+pub fn announce_match_winner(id: usize) {
+    println!("Player {id} wins the match!");
+}
+
+pub fn announce_seed(seed: u64) {
+    println!("Seed: {seed}, pass --seed {seed} to shuffle the same decks again");
+}
+
+pub fn announce_saved_match(path: &std::path::Path) {
+    let path = path.display();
+    println!("The match was saved to {path}, resume it with --load {path}");
+}
+
+pub fn announce_replay_file(path: &std::path::Path) {
+    let path = path.display();
+    println!("The match was recorded to {path}, watch it again with: uno replay {path}");
+}
+
+pub fn print_scoreboard(scores: &[usize], target_score: usize) {
+    println!("Scoreboard (first to {target_score} wins):");
+    for (id, score) in scores.iter().enumerate() {
+        println!("Player {id}: {score}");
+    }
+}
+
+pub fn print_simulation_report(report: &simulation::Report) {
+    println!("Games played: {}", report.num_of_games);
+    println!("Unfinished games: {}", report.unfinished);
+    println!("Average turns per game: {:.1}", report.average_turns());
+    println!(
+        "Average draw pile refills per game: {:.2}",
+        report.average_refills()
+    );
+    println!("Win rate per seat:");
+    for (seat, name) in report.seats.iter().enumerate() {
+        println!(
+            "Player {seat} ({name}): {:.1}%",
+            report.win_rate(seat) * 100.0
+        );
+    }
+    println!("Win rate per strategy:");
+    for (seat, name) in report.seats.iter().enumerate() {
+        if !report.seats[..seat].contains(name) {
+            println!("{name}: {:.1}%", report.win_rate_of(name) * 100.0);
+        }
+    }
+}
+
+pub fn print_tournament_standings(standings: &[tournament::Standing]) {
+    println!(
+        "{:<4} {:<10} {:>7} {:>7} {:>9} {:>17} {:>6}",
+        "Rank", "Strategy", "Games", "Wins", "Win rate", "95% CI", "Elo"
+    );
+    for (rank, standing) in standings.iter().enumerate() {
+        let (low, high) = standing.confidence_interval;
+        println!(
+            "{:<4} {:<10} {:>7} {:>7} {:>8.1}% {:>7.1}% - {:>5.1}% {:>6.0}",
+            rank + 1,
+            standing.difficulty.to_string(),
+            standing.games,
+            standing.wins,
+            standing.win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0,
+            standing.elo
+        );
+    }
+}
+
+pub fn wait_for_next_round() {
+    println!("Press Enter to start the next round");
+    let _ = io::stdin().read_line(&mut String::new());
+}
+
+/// Plays rounds until a player reaches the target score, showing the scoreboard
+/// after every round, and writes the recording of the match to `replay_file`, if any,
+/// after every round. Returns the ID of the player who wins the match, or `None` if the
+/// human player suspends it to save it.
+pub fn play_match(
+    uno_match: &mut score::Match,
+    recorder: &replay::Recorder,
+    replay_file: Option<&std::path::Path>,
+) -> Result<Option<usize>, String> {
+    loop {
+        if let (false, Some(seed)) = (uno_match.is_suspended(), uno_match.get_round_seed()) {
+            recorder.start_round(seed, uno_match.get_dealer(), uno_match.get_undo_actors());
+        }
+        let winner = uno_match.play_round()?;
+        if let Some(replay_file) = replay_file {
+            recorder.get_replay().save(replay_file)?;
+        }
+        if let Some(winner) = winner {
+            announce_winner(winner);
+        }
+        if uno_match.is_suspended() {
+            return Ok(None);
+        }
+        print_scoreboard(uno_match.get_scores(), uno_match.get_target_score());
+        if let Some(winner) = uno_match.get_winner() {
+            return Ok(Some(winner));
+        }
+        wait_for_next_round();
+    }
+}
+
+/// Moves through the frames of a replay.
+#[derive(Debug, PartialEq)]
+enum Navigation {
+    Next,
+    Back,
+    Quit,
+}
+
+/// Shows the frames of a replay one at a time, with every player's hand, until the
+/// user quits.
+pub fn view_replay(replay: &replay::Replay, frames: &[replay::Frame]) {
+    let mut index = 0;
+    let mut input = String::new();
+    while let Some(frame) = frames.get(index) {
+        clear_terminal();
+        print_frame(&replay.players, frame);
+        println!(
+            "{NEXT} or Enter: Next turn, {BACK}: Previous turn, {QUIT}: Quit ({}/{})",
+            index + 1,
+            frames.len()
+        );
+        input.clear();
+        if !matches!(io::stdin().read_line(&mut input), Ok(n) if n > 0) {
+            return;
+        }
+        match check_navigation_input(&input) {
+            Ok(Navigation::Next) => index = (index + 1).min(frames.len() - 1),
+            Ok(Navigation::Back) => index = index.saturating_sub(1),
+            Ok(Navigation::Quit) => return,
+            Err(()) => {}
+        }
+    }
+}
+
+fn check_navigation_input(input: &str) -> Result<Navigation, ()> {
+    match input.trim() {
+        "" | NEXT => Ok(Navigation::Next),
+        BACK => Ok(Navigation::Back),
+        QUIT => Ok(Navigation::Quit),
+        _ => Err(()),
+    }
+}
+
+fn print_frame(players: &[String], frame: &replay::Frame) {
+    let name = |id: usize| players.get(id).map_or("", String::as_str).to_string();
+    println!(
+        "Round {}, turn {}: Player {} ({}) to play",
+        frame.round + 1,
+        frame.turn + 1,
+        frame.player,
+        name(frame.player)
+    );
+    println!("card on top: {}", frame.top_card);
+    if let Some(colour) = frame.colour_in_play.filter(|c| *c != frame.top_card.colour) {
+        println!("colour in play: {colour}");
+    }
+    let direction = if frame.is_flow_clockwise {
+        "clockwise"
+    } else {
+        "counterclockwise"
+    };
+    println!("Play goes {direction}");
+    println!("Number of cards in the draw pile: {}", frame.draw_pile_size);
+    for (id, hand) in frame.hands.iter().enumerate() {
+        let cards: Vec<String> = hand.iter().map(|card| card.to_string()).collect();
+        println!("Player {id} ({}): {}", name(id), cards.join(", "));
+    }
+    if !frame.events.is_empty() {
+        println!("What happened:");
+        for event in &frame.events {
+            println!("  {}", describe_event(event));
+        }
+    }
+}
+
+fn describe_event(event: &event::Event) -> String {
+    match event {
+        event::Event::CardPlayed { player, card } => format!("Player {player} played {card}"),
+        event::Event::CardDrawn { player, card } => format!("Player {player} drew {card}"),
+        event::Event::ColourChosen { player, colour } => {
+            format!("Player {player} chose {colour}")
+        }
+        event::Event::DirectionReversed { is_flow_clockwise } => {
+            let direction = if *is_flow_clockwise {
+                "clockwise"
+            } else {
+                "counterclockwise"
+            };
+            format!("Play now goes {direction}")
+        }
+        event::Event::PlayerSkipped { player } => format!("Player {player} is skipped"),
+        event::Event::PenaltyApplied {
+            player,
+            num_of_cards,
+        } => format!("Player {player} has to draw {num_of_cards} cards"),
+        event::Event::DeckReshuffled { .. } => {
+            String::from("The discard pile was shuffled into the draw pile")
+        }
+        event::Event::RoundWon { player } => format!("Player {player} wins the round!"),
+    }
+}
+
 pub fn get_user_wild_colour() -> card::Colour {
     let mut input = String::new();
 
@@ -113,10 +534,8 @@ pub fn get_user_wild_colour() -> card::Colour {
 
     loop {
         if io::stdin().read_line(&mut input).is_ok() {
-            if let Ok(str) = input.trim().parse::<String>() {
-                if let Ok(c) = check_colour_input(&str) {
-                    return c;
-                }
+            if let Ok(c) = check_colour_input(input.trim()) {
+                return c;
             }
         }
         input.clear();
@@ -133,31 +552,121 @@ fn check_colour_input(input: &str) -> Result<card::Colour, ()> {
     }
 }
 
+pub fn get_user_index() -> usize {
+    let mut input = String::new();
+
+    loop {
+        if io::stdin().read_line(&mut input).is_ok() {
+            if let Ok(index) = input.trim().parse::<usize>() {
+                return index;
+            }
+        }
+        input.clear();
+    }
+}
+
+pub fn get_user_confirmation() -> bool {
+    let mut input = String::new();
+
+    loop {
+        if io::stdin().read_line(&mut input).is_ok() {
+            if let Ok(answer) = check_confirmation_input(input.trim()) {
+                return answer;
+            }
+        }
+        input.clear();
+    }
+}
+
+fn check_confirmation_input(input: &str) -> Result<bool, ()> {
+    match input {
+        YES => Ok(true),
+        NO => Ok(false),
+        _ => Err(()),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     #[test]
     fn test_check_turn_action_input_ok_index() {
-        assert_eq!(check_turn_action_input("1"), Ok(actor::UserAction::Play(1)));
         assert_eq!(
-            check_turn_action_input("001"),
-            Ok(actor::UserAction::Play(1))
+            check_turn_action_input("1", false),
+            Ok(TurnInput::Action(actor::UserAction::Play(1)))
+        );
+        assert_eq!(
+            check_turn_action_input("001", false),
+            Ok(TurnInput::Action(actor::UserAction::Play(1)))
         );
         assert_eq!(
-            check_turn_action_input(" 1 "),
-            Ok(actor::UserAction::Play(1))
+            check_turn_action_input(" 1 ", false),
+            Ok(TurnInput::Action(actor::UserAction::Play(1)))
         );
     }
 
     #[test]
     fn test_check_turn_action_input_ok_draw() {
-        assert_eq!(check_turn_action_input(DRAW), Ok(actor::UserAction::Draw));
+        assert_eq!(
+            check_turn_action_input(DRAW, false),
+            Ok(TurnInput::Action(actor::UserAction::Draw))
+        );
+    }
+
+    #[test]
+    fn test_check_turn_action_input_ok_uno() {
+        assert_eq!(
+            check_turn_action_input(UNO, false),
+            Ok(TurnInput::Action(actor::UserAction::Uno))
+        );
+        assert_eq!(
+            check_turn_action_input(" uno\n", false),
+            Ok(TurnInput::Action(actor::UserAction::Uno))
+        );
+    }
+
+    #[test]
+    fn test_check_turn_action_input_ok_pass() {
+        assert_eq!(
+            check_turn_action_input(PASS, false),
+            Ok(TurnInput::Action(actor::UserAction::Pass))
+        );
+    }
+
+    #[test]
+    fn test_check_turn_action_input_ok_save() {
+        assert_eq!(
+            check_turn_action_input(SAVE, false),
+            Ok(TurnInput::Command(actor::Command::Suspend))
+        );
+    }
+
+    #[test]
+    fn test_check_turn_action_input_ok_undo() {
+        assert_eq!(
+            check_turn_action_input(UNDO, true),
+            Ok(TurnInput::Command(actor::Command::Undo))
+        );
+    }
+
+    #[test]
+    fn test_check_turn_action_input_err_undo_outside_practice() {
+        assert_eq!(check_turn_action_input(UNDO, false), Err(()));
+    }
+
+    #[test]
+    fn test_check_navigation_input() {
+        assert_eq!(check_navigation_input("\n"), Ok(Navigation::Next));
+        assert_eq!(check_navigation_input(NEXT), Ok(Navigation::Next));
+        assert_eq!(check_navigation_input(" b "), Ok(Navigation::Back));
+        assert_eq!(check_navigation_input(QUIT), Ok(Navigation::Quit));
+        assert_eq!(check_navigation_input("x"), Err(()));
     }
 
     #[test]
     fn test_check_turn_action_input_err() {
-        assert_eq!(check_turn_action_input("a"), Err(()));
+        assert_eq!(check_turn_action_input("a", false), Err(()));
     }
 
     #[test]
@@ -172,4 +681,25 @@ mod test {
     fn test_check_colour_input_err() {
         assert_eq!(check_colour_input("a"), Err(()));
     }
+
+    #[test]
+    fn test_check_confirmation_input_ok() {
+        assert_eq!(check_confirmation_input(YES), Ok(true));
+        assert_eq!(check_confirmation_input(NO), Ok(false));
+    }
+
+    #[test]
+    fn test_check_confirmation_input_err() {
+        assert_eq!(check_confirmation_input("a"), Err(()));
+    }
+
+    #[test]
+    fn test_hand_to_string() {
+        let card = card::Card {
+            colour: card::Colour::Red,
+            value: card::Value::Number(1),
+        };
+        let expected = format!("00: {card}\n01: {card}\n02: {card}", card = card);
+        assert_eq!(hand_to_string(&[card, card, card]), expected);
+    }
 }
@@ -0,0 +1,32 @@
+use std::process::Command;
+
+fn simulate_with_bot(bot: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_uno"))
+        .args(["simulate", "-n", "5", "--seats", "normal", "-s", "1"])
+        .args(["--bot", bot, "--bot-timeout", "200"])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_echo_bot_plays_games() {
+    let output = simulate_with_bot(env!("CARGO_BIN_EXE_echo_bot"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Games played: 5"));
+    assert!(stdout.contains("Unfinished games: 0"));
+}
+
+#[test]
+fn test_bot_that_exits_is_replaced_by_the_ai() {
+    let output = simulate_with_bot("true");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Games played: 5"));
+}
+
+#[test]
+fn test_missing_bot_is_an_error() {
+    let output = simulate_with_bot("./no-such-bot");
+    assert!(!output.status.success());
+}